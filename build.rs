@@ -0,0 +1,346 @@
+//! Generates the syntax kinds and the typed syntax tree nodes from
+//! `src/syntax/rexx.ungram`.
+
+use std::fmt::Write;
+use std::{env, fs, path::Path};
+
+use ungrammar::{Grammar, Rule};
+
+const GRAMMAR: &str = "src/syntax/rexx.ungram";
+
+/// Token kinds that the grammar leaves out: trivia, which may come before
+/// any token, and unknown characters.
+const EXTRA_TOKENS: &[&str] = &["Whitespace", "Comment", "Continuation", "Unknown"];
+const TRIVIA: &[&str] = &["Whitespace", "Comment", "Continuation"];
+
+/// The kind names of the punctuation tokens in the grammar.
+const PUNCTUATION: &[(&str, &str)] = &[
+    (":", "Colon"),
+    (";", "Semicolon"),
+    ("=", "Eq"),
+    (",", "Comma"),
+    ("(", "LParen"),
+    (")", "RParen"),
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed={GRAMMAR}");
+    println!("cargo:rerun-if-changed=build.rs");
+    let source = fs::read_to_string(GRAMMAR).unwrap();
+    let grammar = source.parse::<Grammar>().unwrap();
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("syntax.rs"), generate(&grammar)).unwrap();
+}
+
+enum Field {
+    Token {
+        name: String,
+        kind: String,
+    },
+    Node {
+        name: String,
+        ty: String,
+        many: bool,
+        /// How many children of the same type come before this one, as in
+        /// `lhs:Expr 'operator' rhs:Expr`.
+        index: usize,
+    },
+}
+
+impl Field {
+    fn name(&self) -> &str {
+        match self {
+            Field::Token { name, .. } | Field::Node { name, .. } => name,
+        }
+    }
+}
+
+fn generate(grammar: &Grammar) -> String {
+    let mut out = String::new();
+    let mut tokens = EXTRA_TOKENS
+        .iter()
+        .map(|kind| (kind.to_string(), None))
+        .collect::<Vec<_>>();
+    for token in grammar.tokens() {
+        let text = &grammar[token].name;
+        let fixed = PUNCTUATION.iter().any(|(punct, _)| punct == text);
+        tokens.push((token_kind(text), fixed.then(|| text.clone())));
+    }
+    let is_enum = |rule: &Rule| match rule {
+        Rule::Alt(alternatives) => alternatives
+            .iter()
+            .all(|rule| matches!(rule, Rule::Node(_))),
+        _ => false,
+    };
+    let nodes = grammar
+        .iter()
+        .filter(|node| !is_enum(&grammar[*node].rule))
+        .map(|node| grammar[node].name.clone())
+        .collect::<Vec<_>>();
+
+    writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]").unwrap();
+    writeln!(out, "pub enum SyntaxKind {{").unwrap();
+    for (kind, _) in &tokens {
+        writeln!(out, "    {kind},").unwrap();
+    }
+    for node in &nodes {
+        writeln!(out, "    {node},").unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "impl SyntaxKind {{").unwrap();
+    writeln!(out, "    pub fn is_trivia(self) -> bool {{").unwrap();
+    writeln!(out, "        matches!(self, {})", variants(TRIVIA)).unwrap();
+    writeln!(out, "    }}\n").unwrap();
+    writeln!(out, "    pub fn is_token(self) -> bool {{").unwrap();
+    let token_kinds = tokens
+        .iter()
+        .map(|(kind, _)| kind.as_str())
+        .collect::<Vec<_>>();
+    writeln!(out, "        matches!(self, {})", variants(&token_kinds)).unwrap();
+    writeln!(out, "    }}\n").unwrap();
+    writeln!(out, "    /// The text of a punctuation token.").unwrap();
+    writeln!(
+        out,
+        "    pub fn fixed_text(self) -> Option<&'static str> {{"
+    )
+    .unwrap();
+    writeln!(out, "        match self {{").unwrap();
+    for (kind, text) in &tokens {
+        if let Some(text) = text {
+            // JSON string escapes are also valid in Rust string literals.
+            let mut literal = String::new();
+            write_json::string(&mut literal, text);
+            writeln!(out, "            SyntaxKind::{kind} => Some({literal}),").unwrap();
+        }
+    }
+    writeln!(out, "            _ => None,").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    for node in grammar.iter() {
+        let data = &grammar[node];
+        if is_enum(&data.rule) {
+            generate_enum(grammar, &data.name, &data.rule, &mut out);
+        } else {
+            let mut fields = Vec::new();
+            collect_fields(grammar, &data.rule, false, &mut fields);
+            generate_struct(&data.name, &fields, &mut out);
+        }
+    }
+    out
+}
+
+fn generate_struct(name: &str, fields: &[Field], out: &mut String) {
+    writeln!(out, "\n#[derive(Debug, Clone, PartialEq)]").unwrap();
+    writeln!(out, "pub struct {name} {{").unwrap();
+    writeln!(out, "    pub(crate) syntax: SyntaxNode,").unwrap();
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "impl {name} {{").unwrap();
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            writeln!(out).unwrap();
+        }
+        match field {
+            Field::Token { name, kind } => {
+                writeln!(out, "    pub fn {name}(&self) -> Option<SyntaxToken> {{").unwrap();
+                writeln!(
+                    out,
+                    "        support::token(&self.syntax, SyntaxKind::{kind})"
+                )
+                .unwrap();
+            }
+            Field::Node {
+                name,
+                ty,
+                many: true,
+                ..
+            } => {
+                writeln!(out, "    pub fn {name}(&self) -> AstChildren<{ty}> {{").unwrap();
+                writeln!(out, "        support::children(&self.syntax)").unwrap();
+            }
+            Field::Node {
+                name, ty, index: 0, ..
+            } => {
+                writeln!(out, "    pub fn {name}(&self) -> Option<{ty}> {{").unwrap();
+                writeln!(out, "        support::child(&self.syntax)").unwrap();
+            }
+            Field::Node {
+                name, ty, index, ..
+            } => {
+                writeln!(out, "    pub fn {name}(&self) -> Option<{ty}> {{").unwrap();
+                writeln!(out, "        support::children(&self.syntax).nth({index})").unwrap();
+            }
+        }
+        writeln!(out, "    }}").unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "impl AstNode for {name} {{").unwrap();
+    writeln!(out, "    fn can_cast(kind: SyntaxKind) -> bool {{").unwrap();
+    writeln!(out, "        kind == SyntaxKind::{name}").unwrap();
+    writeln!(out, "    }}\n").unwrap();
+    writeln!(out, "    fn cast(syntax: SyntaxNode) -> Option<Self> {{").unwrap();
+    writeln!(
+        out,
+        "        Self::can_cast(syntax.kind()).then_some({name} {{ syntax }})"
+    )
+    .unwrap();
+    writeln!(out, "    }}\n").unwrap();
+    writeln!(out, "    fn syntax(&self) -> &SyntaxNode {{").unwrap();
+    writeln!(out, "        &self.syntax").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+}
+
+fn generate_enum(grammar: &Grammar, name: &str, rule: &Rule, out: &mut String) {
+    let Rule::Alt(alternatives) = rule else {
+        unreachable!()
+    };
+    let variants = alternatives
+        .iter()
+        .map(|rule| match rule {
+            Rule::Node(node) => grammar[*node].name.clone(),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    writeln!(out, "\n#[derive(Debug, Clone, PartialEq)]").unwrap();
+    writeln!(out, "pub enum {name} {{").unwrap();
+    for variant in &variants {
+        writeln!(out, "    {variant}({variant}),").unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    let kinds = variants.iter().map(String::as_str).collect::<Vec<_>>();
+    writeln!(out, "impl AstNode for {name} {{").unwrap();
+    writeln!(out, "    fn can_cast(kind: SyntaxKind) -> bool {{").unwrap();
+    writeln!(out, "        matches!(kind, {})", self::variants(&kinds)).unwrap();
+    writeln!(out, "    }}\n").unwrap();
+    writeln!(out, "    fn cast(syntax: SyntaxNode) -> Option<Self> {{").unwrap();
+    writeln!(out, "        let node = match syntax.kind() {{").unwrap();
+    for variant in &variants {
+        writeln!(
+            out,
+            "            SyntaxKind::{variant} => {name}::{variant}({variant} {{ syntax }}),"
+        )
+        .unwrap();
+    }
+    writeln!(out, "            _ => return None,").unwrap();
+    writeln!(out, "        }};").unwrap();
+    writeln!(out, "        Some(node)").unwrap();
+    writeln!(out, "    }}\n").unwrap();
+    writeln!(out, "    fn syntax(&self) -> &SyntaxNode {{").unwrap();
+    writeln!(out, "        match self {{").unwrap();
+    for variant in &variants {
+        writeln!(out, "            {name}::{variant}(node) => &node.syntax,").unwrap();
+    }
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+}
+
+/// Collects the accessors of a node. Tokens inside a repetition get none, as
+/// only the first of them could be reached.
+fn collect_fields(grammar: &Grammar, rule: &Rule, many: bool, fields: &mut Vec<Field>) {
+    let mut field = match rule {
+        // A label on an optional rule, such as `condition:Expr?`, names it.
+        Rule::Labeled { label, rule } => match unwrap_opt(rule) {
+            Rule::Token(token) if !many => Field::Token {
+                name: format!("{label}_token"),
+                kind: token_kind(&grammar[*token].name),
+            },
+            Rule::Node(node) => Field::Node {
+                name: label.clone(),
+                ty: grammar[*node].name.clone(),
+                many,
+                index: 0,
+            },
+            rule => return collect_fields(grammar, rule, many, fields),
+        },
+        Rule::Token(token) if !many => {
+            let kind = token_kind(&grammar[*token].name);
+            Field::Token {
+                name: format!("{}_token", snake_case(&kind)),
+                kind,
+            }
+        }
+        Rule::Token(_) => return,
+        Rule::Node(node) => {
+            let ty = grammar[*node].name.clone();
+            let name = snake_case(&ty);
+            Field::Node {
+                name: if many { format!("{name}s") } else { name },
+                ty,
+                many,
+                index: 0,
+            }
+        }
+        Rule::Seq(rules) | Rule::Alt(rules) => {
+            for rule in rules {
+                collect_fields(grammar, rule, many, fields);
+            }
+            return;
+        }
+        Rule::Opt(rule) => return collect_fields(grammar, rule, many, fields),
+        Rule::Rep(rule) => return collect_fields(grammar, rule, true, fields),
+    };
+    if fields
+        .iter()
+        .any(|existing| existing.name() == field.name())
+    {
+        return;
+    }
+    if let Field::Node {
+        ty,
+        many: false,
+        index,
+        ..
+    } = &mut field
+    {
+        *index = fields
+            .iter()
+            .filter(|existing| matches!(existing, Field::Node { ty: other, .. } if other == ty))
+            .count();
+    }
+    fields.push(field);
+}
+
+fn unwrap_opt(rule: &Rule) -> &Rule {
+    match rule {
+        Rule::Opt(rule) => rule,
+        rule => rule,
+    }
+}
+
+/// The kind name of a grammar token: `':'` is `Colon`, `'symbol'` is
+/// `Symbol`.
+fn token_kind(text: &str) -> String {
+    if let Some((_, kind)) = PUNCTUATION.iter().find(|(punct, _)| *punct == text) {
+        return kind.to_string();
+    }
+    let mut chars = text.chars();
+    let first = chars.next().unwrap().to_ascii_uppercase();
+    std::iter::once(first).chain(chars).collect()
+}
+
+fn snake_case(name: &str) -> String {
+    let mut result = String::new();
+    for (i, ch) in name.chars().enumerate() {
+        if ch.is_ascii_uppercase() && i > 0 {
+            result.push('_');
+        }
+        result.push(ch.to_ascii_lowercase());
+    }
+    result
+}
+
+fn variants(kinds: &[&str]) -> String {
+    kinds
+        .iter()
+        .map(|kind| format!("SyntaxKind::{kind}"))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
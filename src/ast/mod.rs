@@ -0,0 +1,537 @@
+pub mod visit;
+pub mod visit_mut;
+
+use serde::{Deserialize, Serialize};
+
+use crate::lexer::{Range, Token};
+
+// AST fill follow the BNF from the stasndard for now.
+/// The source range of a node, from its first to its last token. Blanks,
+/// comments and the clause terminator are left out.
+pub type Span = Range;
+
+/// A token or a node of the AST, which knows where it is in the source.
+pub trait Spanned {
+    fn span(&self) -> &Span;
+
+    /// The text of the token or node in the source it was parsed from.
+    fn text<'s>(&self, source: &'s str) -> &'s str {
+        let span = self.span();
+        &source[span.start.index..span.end.index]
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Instruction {
+    pub kind: InstructionKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum InstructionKind {
+    Label(Token),
+    /// `target = expression`, where the target is a simple, stem or
+    /// compound symbol.
+    Assignment {
+        target: Token,
+        expression: Expression,
+    },
+
+    /// `DO ... END` in all its forms; a simple DO group has neither a
+    /// repetitor nor a conditional.
+    Do {
+        repetitor: Option<Box<Repetitor>>,
+        conditional: Option<Conditional>,
+        body: Vec<Instruction>,
+        /// The symbol after `END`, if any.
+        end_name: Option<Token>,
+    },
+
+    If {
+        condition: Expression,
+        then_branch: Box<Instruction>,
+        else_branch: Option<Box<Instruction>>,
+    },
+    Select {
+        whens: Vec<When>,
+        otherwise: Option<Vec<Instruction>>,
+    },
+    /// A clause that is only an expression, whose value is passed to the
+    /// environment as a command.
+    Command(Expression),
+
+    // Kayword Instructions
+    /// `ADDRESS`, `ADDRESS environment [command]` or
+    /// `ADDRESS [VALUE] expression`.
+    Address(AddressTarget),
+    /// `CALL name [args]`, `CALL ON condition [NAME label]` or
+    /// `CALL OFF condition`. The ON and OFF forms have no arguments.
+    Call {
+        target: CallTarget,
+        args: Vec<Option<Expression>>,
+    },
+    /// `ARG template_list`, short for `PARSE UPPER ARG template_list`.
+    Arg(Vec<Template>),
+    Drop(Vec<VariableReference>),
+    Exit(Option<Expression>),
+    Interpret(Expression),
+    /// `ITERATE [name]`, where the name is the control variable of a loop.
+    Iterate(Option<Token>),
+    /// `LEAVE [name]`, where the name is the control variable of a loop.
+    Leave(Option<Token>),
+    Nop,
+    Numeric(NumericSetting),
+    Options(Expression),
+    Parse {
+        case: Option<ParseCase>,
+        caseless: bool,
+        source: ParseSource,
+        templates: Vec<Template>,
+    },
+    /// `PROCEDURE [EXPOSE variable_list]`; the list is empty without
+    /// EXPOSE.
+    Procedure {
+        expose: Vec<VariableReference>,
+    },
+    /// `PULL template_list`, short for `PARSE UPPER PULL template_list`.
+    Pull(Vec<Template>),
+    Push(Option<Expression>),
+    Queue(Option<Expression>),
+    Return(Option<Expression>),
+    Say(Option<Expression>),
+    Signal(SignalTarget),
+    Trace(TraceSetting),
+
+    Unknown(Token),
+}
+
+/// `WHEN condition THEN instruction` in a SELECT.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct When {
+    pub condition: Expression,
+    pub instruction: Instruction,
+    pub span: Span,
+}
+
+/// The name of a called routine or function. A literal string name is
+/// never resolved to an internal label, only to a built-in or external
+/// routine.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum RoutineName {
+    Symbol(Token),
+    Literal(Token),
+}
+
+/// The target of a CALL: the routine name without its arguments, or the
+/// ON or OFF trap up to the end of the clause.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CallTarget {
+    pub kind: CallTargetKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum CallTargetKind {
+    /// An internal label, a built-in function or an external routine.
+    Routine(RoutineName),
+    /// `ON condition [NAME label]`
+    On {
+        condition: Token,
+        name: Option<Token>,
+    },
+    /// `OFF condition`
+    Off { condition: Token },
+}
+
+/// The target of a SIGNAL, from the label or the VALUE, ON or OFF
+/// keyword to the end of the clause.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignalTarget {
+    pub kind: SignalTargetKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum SignalTargetKind {
+    /// `SIGNAL label`
+    Label(Token),
+    /// `SIGNAL [VALUE] expression`, a label computed at run time.
+    Value(Expression),
+    /// `ON condition [NAME label]`
+    On {
+        condition: Token,
+        name: Option<Token>,
+    },
+    /// `OFF condition`
+    Off { condition: Token },
+}
+
+/// The operands of an ADDRESS. The span is empty for ADDRESS alone.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddressTarget {
+    pub kind: AddressTargetKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum AddressTargetKind {
+    /// `ADDRESS` alone, which swaps the current and previous environments.
+    Swap,
+    /// `ADDRESS environment [command]`. Without a command the environment
+    /// becomes the default for later commands.
+    Environment {
+        name: Token,
+        command: Option<Expression>,
+    },
+    /// `ADDRESS [VALUE] expression`
+    Value(Expression),
+}
+
+/// A name in the variable list of DROP or PROCEDURE EXPOSE. The span
+/// of an indirect reference includes its parentheses.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VariableReference {
+    pub kind: VariableReferenceKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum VariableReferenceKind {
+    /// A simple, stem or compound symbol.
+    Variable(Token),
+    /// `(name)`, where the value of the variable is a blank-separated list
+    /// of further names.
+    Indirect(Token),
+}
+
+/// The operands of a NUMERIC, from DIGITS, FUZZ or FORM on.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NumericSetting {
+    pub kind: NumericSettingKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum NumericSettingKind {
+    /// `NUMERIC DIGITS [expression]`
+    Digits(Option<Expression>),
+    /// `NUMERIC FUZZ [expression]`
+    Fuzz(Option<Expression>),
+    /// `NUMERIC FORM [form]`; FORM alone resets it to SCIENTIFIC.
+    Form(Option<NumericForm>),
+}
+
+/// The setting after NUMERIC FORM, including any VALUE keyword.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NumericForm {
+    pub kind: NumericFormKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum NumericFormKind {
+    Scientific,
+    Engineering,
+    /// `[VALUE] expression`
+    Value(Expression),
+}
+
+/// The operands of a TRACE. The span is empty for TRACE alone.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraceSetting {
+    pub kind: TraceSettingKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum TraceSettingKind {
+    /// `TRACE` alone, which restores the default setting.
+    Default,
+    /// A symbol or string such as `?R`, `Off` or `'I'`, or a whole number.
+    Setting(Token),
+    /// `TRACE [VALUE] expression`
+    Value(Expression),
+}
+
+/// The case conversion of `PARSE UPPER` and `PARSE LOWER`.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum ParseCase {
+    Upper,
+    Lower,
+}
+
+/// Where PARSE takes its string from: the keyword after any UPPER, LOWER
+/// or CASELESS and its operands, including the WITH of PARSE VALUE.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParseSource {
+    pub kind: ParseSourceKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ParseSourceKind {
+    Arg,
+    Linein,
+    Pull,
+    Source,
+    /// `VALUE [expression] WITH`
+    Value(Option<Expression>),
+    /// `VAR name`
+    Var(Token),
+    Version,
+}
+
+/// One template of a template list; PARSE ARG applies each template to
+/// the matching argument.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Template {
+    pub elements: Vec<TemplateElement>,
+    /// Empty for an empty template, at the position where it would start.
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateElement {
+    pub kind: TemplateElementKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum TemplateElementKind {
+    /// A variable that receives part of the string.
+    Target(Token),
+    /// `.`, which discards part of the string.
+    Placeholder(Token),
+    /// `'string'`
+    LiteralPattern(Token),
+    /// `(expression)`, a pattern whose string is the value of the expression.
+    VariablePattern(Expression),
+    /// `10`, `=10` or `=(expression)`
+    AbsolutePosition(Expression),
+    /// `+10`, `-10`, `+(expression)` or `-(expression)`
+    RelativePosition { forward: bool, offset: Expression },
+}
+
+/// The part of a DO clause that says how often the group runs.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Repetitor {
+    pub kind: RepetitorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum RepetitorKind {
+    /// `DO i = start [TO to] [BY by] [FOR for]`; the keywords may come in
+    /// any order.
+    Controlled {
+        variable: Token,
+        start: Expression,
+        to: Option<Box<Expression>>,
+        by: Option<Box<Expression>>,
+        count: Option<Box<Expression>>,
+    },
+    /// `DO n`
+    Count(Expression),
+    /// `DO FOREVER`
+    Forever,
+}
+
+/// `WHILE expression` or `UNTIL expression` in a DO clause.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Conditional {
+    pub kind: ConditionalKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ConditionalKind {
+    While(Expression),
+    Until(Expression),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum ExpressionKind {
+    /// A string, including hexadecimal and binary strings.
+    Literal(Token),
+    /// A number, constant symbol or variable.
+    Symbol(Token),
+    /// `name(arg, ...)`; omitted arguments are `None`.
+    FunctionCall {
+        name: RoutineName,
+        args: Vec<Option<Expression>>,
+    },
+    Parenthesized(Box<Expression>),
+    Prefix {
+        operator: PrefixOperator,
+        operand: Box<Expression>,
+    },
+    Binary {
+        operator: BinaryOperator,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum PrefixOperator {
+    Plus,
+    Minus,
+    Not,
+}
+
+/// Binary operators, from the highest to the lowest priority group.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum BinaryOperator {
+    Power,
+
+    Multiply,
+    Divide,
+    IntegerDivide,
+    Remainder,
+
+    Add,
+    Subtract,
+
+    /// Concatenation with a blank: `a b`
+    BlankConcatenate,
+    /// Concatenation without a blank: `a'b'`
+    AbuttalConcatenate,
+    /// `a || b`
+    Concatenate,
+
+    Equal,
+    NotEqual,
+    Greater,
+    Less,
+    GreaterOrEqual,
+    LessOrEqual,
+    NotGreater,
+    NotLess,
+    StrictEqual,
+    StrictNotEqual,
+    StrictGreater,
+    StrictLess,
+    StrictGreaterOrEqual,
+    StrictLessOrEqual,
+    StrictNotGreater,
+    StrictNotLess,
+
+    And,
+
+    Or,
+    Xor,
+}
+
+impl Spanned for Token {
+    fn span(&self) -> &Span {
+        &self.range
+    }
+}
+
+impl Spanned for Program {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for Instruction {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for When {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for Template {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for TemplateElement {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for Expression {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for CallTarget {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for SignalTarget {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for AddressTarget {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for VariableReference {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for NumericSetting {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for NumericForm {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for TraceSetting {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for ParseSource {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for Repetitor {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Spanned for Conditional {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
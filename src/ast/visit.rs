@@ -0,0 +1,406 @@
+//! Read-only traversal of the AST. Each `visit_*` method of [`Visitor`]
+//! calls the `walk_*` function of the same node by default, which visits
+//! the children of the node in source order. An override that still wants
+//! the children calls the `walk_*` function itself.
+
+use crate::lexer::Token;
+
+use super::{
+    AddressTarget, AddressTargetKind, CallTarget, CallTargetKind, Conditional, ConditionalKind,
+    Expression, ExpressionKind, Instruction, InstructionKind, NumericForm, NumericFormKind,
+    NumericSetting, NumericSettingKind, ParseSource, ParseSourceKind, Program, Repetitor,
+    RepetitorKind, RoutineName, SignalTarget, SignalTargetKind, Template, TemplateElement,
+    TemplateElementKind, TraceSetting, TraceSettingKind, VariableReference, VariableReferenceKind,
+    When,
+};
+
+pub trait Visitor {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_instruction(&mut self, instruction: &Instruction) {
+        walk_instruction(self, instruction);
+    }
+
+    fn visit_when(&mut self, when: &When) {
+        walk_when(self, when);
+    }
+
+    fn visit_repetitor(&mut self, repetitor: &Repetitor) {
+        walk_repetitor(self, repetitor);
+    }
+
+    fn visit_conditional(&mut self, conditional: &Conditional) {
+        walk_conditional(self, conditional);
+    }
+
+    fn visit_address_target(&mut self, target: &AddressTarget) {
+        walk_address_target(self, target);
+    }
+
+    fn visit_call_target(&mut self, target: &CallTarget) {
+        walk_call_target(self, target);
+    }
+
+    fn visit_signal_target(&mut self, target: &SignalTarget) {
+        walk_signal_target(self, target);
+    }
+
+    fn visit_routine_name(&mut self, name: &RoutineName) {
+        walk_routine_name(self, name);
+    }
+
+    fn visit_variable_reference(&mut self, reference: &VariableReference) {
+        walk_variable_reference(self, reference);
+    }
+
+    fn visit_numeric_setting(&mut self, setting: &NumericSetting) {
+        walk_numeric_setting(self, setting);
+    }
+
+    fn visit_numeric_form(&mut self, form: &NumericForm) {
+        walk_numeric_form(self, form);
+    }
+
+    fn visit_trace_setting(&mut self, setting: &TraceSetting) {
+        walk_trace_setting(self, setting);
+    }
+
+    fn visit_parse_source(&mut self, source: &ParseSource) {
+        walk_parse_source(self, source);
+    }
+
+    fn visit_template(&mut self, template: &Template) {
+        walk_template(self, template);
+    }
+
+    fn visit_template_element(&mut self, element: &TemplateElement) {
+        walk_template_element(self, element);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression);
+    }
+
+    /// A token kept in the AST: a name, a symbol or a string, never a
+    /// keyword or an operator.
+    fn visit_token(&mut self, _token: &Token) {}
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    for instruction in &program.instructions {
+        visitor.visit_instruction(instruction);
+    }
+}
+
+pub fn walk_instruction<V: Visitor + ?Sized>(visitor: &mut V, instruction: &Instruction) {
+    match &instruction.kind {
+        InstructionKind::Label(name) | InstructionKind::Unknown(name) => visitor.visit_token(name),
+        InstructionKind::Assignment { target, expression } => {
+            visitor.visit_token(target);
+            visitor.visit_expression(expression);
+        }
+        InstructionKind::Do {
+            repetitor,
+            conditional,
+            body,
+            end_name,
+        } => {
+            if let Some(repetitor) = repetitor {
+                visitor.visit_repetitor(repetitor);
+            }
+            if let Some(conditional) = conditional {
+                visitor.visit_conditional(conditional);
+            }
+            for instruction in body {
+                visitor.visit_instruction(instruction);
+            }
+            if let Some(name) = end_name {
+                visitor.visit_token(name);
+            }
+        }
+        InstructionKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            visitor.visit_expression(condition);
+            visitor.visit_instruction(then_branch);
+            if let Some(else_branch) = else_branch {
+                visitor.visit_instruction(else_branch);
+            }
+        }
+        InstructionKind::Select { whens, otherwise } => {
+            for when in whens {
+                visitor.visit_when(when);
+            }
+            for instruction in otherwise.iter().flatten() {
+                visitor.visit_instruction(instruction);
+            }
+        }
+        InstructionKind::Command(expression)
+        | InstructionKind::Interpret(expression)
+        | InstructionKind::Options(expression) => visitor.visit_expression(expression),
+        InstructionKind::Address(target) => visitor.visit_address_target(target),
+        InstructionKind::Call { target, args } => {
+            visitor.visit_call_target(target);
+            for arg in args.iter().flatten() {
+                visitor.visit_expression(arg);
+            }
+        }
+        InstructionKind::Arg(templates) | InstructionKind::Pull(templates) => {
+            for template in templates {
+                visitor.visit_template(template);
+            }
+        }
+        InstructionKind::Drop(references) | InstructionKind::Procedure { expose: references } => {
+            for reference in references {
+                visitor.visit_variable_reference(reference);
+            }
+        }
+        InstructionKind::Exit(expression)
+        | InstructionKind::Push(expression)
+        | InstructionKind::Queue(expression)
+        | InstructionKind::Return(expression)
+        | InstructionKind::Say(expression) => {
+            if let Some(expression) = expression {
+                visitor.visit_expression(expression);
+            }
+        }
+        InstructionKind::Iterate(name) | InstructionKind::Leave(name) => {
+            if let Some(name) = name {
+                visitor.visit_token(name);
+            }
+        }
+        InstructionKind::Nop => {}
+        InstructionKind::Numeric(setting) => visitor.visit_numeric_setting(setting),
+        InstructionKind::Parse {
+            source, templates, ..
+        } => {
+            visitor.visit_parse_source(source);
+            for template in templates {
+                visitor.visit_template(template);
+            }
+        }
+        InstructionKind::Signal(target) => visitor.visit_signal_target(target),
+        InstructionKind::Trace(setting) => visitor.visit_trace_setting(setting),
+    }
+}
+
+pub fn walk_when<V: Visitor + ?Sized>(visitor: &mut V, when: &When) {
+    visitor.visit_expression(&when.condition);
+    visitor.visit_instruction(&when.instruction);
+}
+
+pub fn walk_repetitor<V: Visitor + ?Sized>(visitor: &mut V, repetitor: &Repetitor) {
+    match &repetitor.kind {
+        RepetitorKind::Controlled {
+            variable,
+            start,
+            to,
+            by,
+            count,
+        } => {
+            visitor.visit_token(variable);
+            visitor.visit_expression(start);
+            for expression in [to, by, count].into_iter().flatten() {
+                visitor.visit_expression(expression);
+            }
+        }
+        RepetitorKind::Count(expression) => visitor.visit_expression(expression),
+        RepetitorKind::Forever => {}
+    }
+}
+
+pub fn walk_conditional<V: Visitor + ?Sized>(visitor: &mut V, conditional: &Conditional) {
+    match &conditional.kind {
+        ConditionalKind::While(expression) | ConditionalKind::Until(expression) => {
+            visitor.visit_expression(expression);
+        }
+    }
+}
+
+pub fn walk_address_target<V: Visitor + ?Sized>(visitor: &mut V, target: &AddressTarget) {
+    match &target.kind {
+        AddressTargetKind::Swap => {}
+        AddressTargetKind::Environment { name, command } => {
+            visitor.visit_token(name);
+            if let Some(command) = command {
+                visitor.visit_expression(command);
+            }
+        }
+        AddressTargetKind::Value(expression) => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_call_target<V: Visitor + ?Sized>(visitor: &mut V, target: &CallTarget) {
+    match &target.kind {
+        CallTargetKind::Routine(name) => visitor.visit_routine_name(name),
+        CallTargetKind::On { condition, name } => {
+            visitor.visit_token(condition);
+            if let Some(name) = name {
+                visitor.visit_token(name);
+            }
+        }
+        CallTargetKind::Off { condition } => visitor.visit_token(condition),
+    }
+}
+
+pub fn walk_signal_target<V: Visitor + ?Sized>(visitor: &mut V, target: &SignalTarget) {
+    match &target.kind {
+        SignalTargetKind::Label(name) => visitor.visit_token(name),
+        SignalTargetKind::Value(expression) => visitor.visit_expression(expression),
+        SignalTargetKind::On { condition, name } => {
+            visitor.visit_token(condition);
+            if let Some(name) = name {
+                visitor.visit_token(name);
+            }
+        }
+        SignalTargetKind::Off { condition } => visitor.visit_token(condition),
+    }
+}
+
+pub fn walk_routine_name<V: Visitor + ?Sized>(visitor: &mut V, name: &RoutineName) {
+    match name {
+        RoutineName::Symbol(token) | RoutineName::Literal(token) => visitor.visit_token(token),
+    }
+}
+
+pub fn walk_variable_reference<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    reference: &VariableReference,
+) {
+    match &reference.kind {
+        VariableReferenceKind::Variable(name) | VariableReferenceKind::Indirect(name) => {
+            visitor.visit_token(name);
+        }
+    }
+}
+
+pub fn walk_numeric_setting<V: Visitor + ?Sized>(visitor: &mut V, setting: &NumericSetting) {
+    match &setting.kind {
+        NumericSettingKind::Digits(expression) | NumericSettingKind::Fuzz(expression) => {
+            if let Some(expression) = expression {
+                visitor.visit_expression(expression);
+            }
+        }
+        NumericSettingKind::Form(form) => {
+            if let Some(form) = form {
+                visitor.visit_numeric_form(form);
+            }
+        }
+    }
+}
+
+pub fn walk_numeric_form<V: Visitor + ?Sized>(visitor: &mut V, form: &NumericForm) {
+    match &form.kind {
+        NumericFormKind::Scientific | NumericFormKind::Engineering => {}
+        NumericFormKind::Value(expression) => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_trace_setting<V: Visitor + ?Sized>(visitor: &mut V, setting: &TraceSetting) {
+    match &setting.kind {
+        TraceSettingKind::Default => {}
+        TraceSettingKind::Setting(token) => visitor.visit_token(token),
+        TraceSettingKind::Value(expression) => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_parse_source<V: Visitor + ?Sized>(visitor: &mut V, source: &ParseSource) {
+    match &source.kind {
+        ParseSourceKind::Value(Some(expression)) => visitor.visit_expression(expression),
+        ParseSourceKind::Var(name) => visitor.visit_token(name),
+        ParseSourceKind::Arg
+        | ParseSourceKind::Linein
+        | ParseSourceKind::Pull
+        | ParseSourceKind::Source
+        | ParseSourceKind::Value(None)
+        | ParseSourceKind::Version => {}
+    }
+}
+
+pub fn walk_template<V: Visitor + ?Sized>(visitor: &mut V, template: &Template) {
+    for element in &template.elements {
+        visitor.visit_template_element(element);
+    }
+}
+
+pub fn walk_template_element<V: Visitor + ?Sized>(visitor: &mut V, element: &TemplateElement) {
+    match &element.kind {
+        TemplateElementKind::Target(token)
+        | TemplateElementKind::Placeholder(token)
+        | TemplateElementKind::LiteralPattern(token) => visitor.visit_token(token),
+        TemplateElementKind::VariablePattern(expression)
+        | TemplateElementKind::AbsolutePosition(expression)
+        | TemplateElementKind::RelativePosition {
+            offset: expression, ..
+        } => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    match &expression.kind {
+        ExpressionKind::Literal(token) | ExpressionKind::Symbol(token) => {
+            visitor.visit_token(token);
+        }
+        ExpressionKind::FunctionCall { name, args } => {
+            visitor.visit_routine_name(name);
+            for arg in args.iter().flatten() {
+                visitor.visit_expression(arg);
+            }
+        }
+        ExpressionKind::Parenthesized(inner) => visitor.visit_expression(inner),
+        ExpressionKind::Prefix { operand, .. } => visitor.visit_expression(operand),
+        ExpressionKind::Binary { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RexxParser;
+
+    /// Collects the source text of every token, and counts instructions.
+    #[derive(Default)]
+    struct Collect {
+        tokens: Vec<Token>,
+        instructions: usize,
+    }
+
+    impl Visitor for Collect {
+        fn visit_instruction(&mut self, instruction: &Instruction) {
+            self.instructions += 1;
+            walk_instruction(self, instruction);
+        }
+
+        fn visit_token(&mut self, token: &Token) {
+            self.tokens.push(token.clone());
+        }
+    }
+
+    #[test]
+    fn visit_nested_groups() {
+        let source = "do i = 1 to n\n  if a then say f(b, -c)\n  else select\n    \
+                      when d then call e (g)\n    otherwise parse var h j +(k) l\n  end\nend i";
+        let mut parser = RexxParser::new(source);
+        let program = parser.parse().unwrap();
+        let mut collect = Collect::default();
+        collect.visit_program(&program);
+        let texts = collect
+            .tokens
+            .iter()
+            .map(|token| parser.get_text(token))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec!["i", "1", "n", "a", "f", "b", "c", "d", "e", "g", "h", "j", "k", "l", "i"]
+        );
+        // DO, IF, SAY, SELECT, CALL and PARSE
+        assert_eq!(collect.instructions, 6);
+    }
+}
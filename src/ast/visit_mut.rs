@@ -0,0 +1,415 @@
+//! Traversal of the AST that may change it in place, like
+//! [`visit`](super::visit) but with mutable references.
+
+use crate::lexer::Token;
+
+use super::{
+    AddressTarget, AddressTargetKind, CallTarget, CallTargetKind, Conditional, ConditionalKind,
+    Expression, ExpressionKind, Instruction, InstructionKind, NumericForm, NumericFormKind,
+    NumericSetting, NumericSettingKind, ParseSource, ParseSourceKind, Program, Repetitor,
+    RepetitorKind, RoutineName, SignalTarget, SignalTargetKind, Template, TemplateElement,
+    TemplateElementKind, TraceSetting, TraceSettingKind, VariableReference, VariableReferenceKind,
+    When,
+};
+
+pub trait VisitorMut {
+    fn visit_program(&mut self, program: &mut Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_instruction(&mut self, instruction: &mut Instruction) {
+        walk_instruction(self, instruction);
+    }
+
+    fn visit_when(&mut self, when: &mut When) {
+        walk_when(self, when);
+    }
+
+    fn visit_repetitor(&mut self, repetitor: &mut Repetitor) {
+        walk_repetitor(self, repetitor);
+    }
+
+    fn visit_conditional(&mut self, conditional: &mut Conditional) {
+        walk_conditional(self, conditional);
+    }
+
+    fn visit_address_target(&mut self, target: &mut AddressTarget) {
+        walk_address_target(self, target);
+    }
+
+    fn visit_call_target(&mut self, target: &mut CallTarget) {
+        walk_call_target(self, target);
+    }
+
+    fn visit_signal_target(&mut self, target: &mut SignalTarget) {
+        walk_signal_target(self, target);
+    }
+
+    fn visit_routine_name(&mut self, name: &mut RoutineName) {
+        walk_routine_name(self, name);
+    }
+
+    fn visit_variable_reference(&mut self, reference: &mut VariableReference) {
+        walk_variable_reference(self, reference);
+    }
+
+    fn visit_numeric_setting(&mut self, setting: &mut NumericSetting) {
+        walk_numeric_setting(self, setting);
+    }
+
+    fn visit_numeric_form(&mut self, form: &mut NumericForm) {
+        walk_numeric_form(self, form);
+    }
+
+    fn visit_trace_setting(&mut self, setting: &mut TraceSetting) {
+        walk_trace_setting(self, setting);
+    }
+
+    fn visit_parse_source(&mut self, source: &mut ParseSource) {
+        walk_parse_source(self, source);
+    }
+
+    fn visit_template(&mut self, template: &mut Template) {
+        walk_template(self, template);
+    }
+
+    fn visit_template_element(&mut self, element: &mut TemplateElement) {
+        walk_template_element(self, element);
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression);
+    }
+
+    /// A token kept in the AST: a name, a symbol or a string, never a
+    /// keyword or an operator.
+    fn visit_token(&mut self, _token: &mut Token) {}
+}
+
+pub fn walk_program<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    for instruction in &mut program.instructions {
+        visitor.visit_instruction(instruction);
+    }
+}
+
+pub fn walk_instruction<V: VisitorMut + ?Sized>(visitor: &mut V, instruction: &mut Instruction) {
+    match &mut instruction.kind {
+        InstructionKind::Label(name) | InstructionKind::Unknown(name) => visitor.visit_token(name),
+        InstructionKind::Assignment { target, expression } => {
+            visitor.visit_token(target);
+            visitor.visit_expression(expression);
+        }
+        InstructionKind::Do {
+            repetitor,
+            conditional,
+            body,
+            end_name,
+        } => {
+            if let Some(repetitor) = repetitor {
+                visitor.visit_repetitor(repetitor);
+            }
+            if let Some(conditional) = conditional {
+                visitor.visit_conditional(conditional);
+            }
+            for instruction in body {
+                visitor.visit_instruction(instruction);
+            }
+            if let Some(name) = end_name {
+                visitor.visit_token(name);
+            }
+        }
+        InstructionKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            visitor.visit_expression(condition);
+            visitor.visit_instruction(then_branch);
+            if let Some(else_branch) = else_branch {
+                visitor.visit_instruction(else_branch);
+            }
+        }
+        InstructionKind::Select { whens, otherwise } => {
+            for when in whens {
+                visitor.visit_when(when);
+            }
+            for instruction in otherwise.iter_mut().flatten() {
+                visitor.visit_instruction(instruction);
+            }
+        }
+        InstructionKind::Command(expression)
+        | InstructionKind::Interpret(expression)
+        | InstructionKind::Options(expression) => visitor.visit_expression(expression),
+        InstructionKind::Address(target) => visitor.visit_address_target(target),
+        InstructionKind::Call { target, args } => {
+            visitor.visit_call_target(target);
+            for arg in args.iter_mut().flatten() {
+                visitor.visit_expression(arg);
+            }
+        }
+        InstructionKind::Arg(templates) | InstructionKind::Pull(templates) => {
+            for template in templates {
+                visitor.visit_template(template);
+            }
+        }
+        InstructionKind::Drop(references) | InstructionKind::Procedure { expose: references } => {
+            for reference in references {
+                visitor.visit_variable_reference(reference);
+            }
+        }
+        InstructionKind::Exit(expression)
+        | InstructionKind::Push(expression)
+        | InstructionKind::Queue(expression)
+        | InstructionKind::Return(expression)
+        | InstructionKind::Say(expression) => {
+            if let Some(expression) = expression {
+                visitor.visit_expression(expression);
+            }
+        }
+        InstructionKind::Iterate(name) | InstructionKind::Leave(name) => {
+            if let Some(name) = name {
+                visitor.visit_token(name);
+            }
+        }
+        InstructionKind::Nop => {}
+        InstructionKind::Numeric(setting) => visitor.visit_numeric_setting(setting),
+        InstructionKind::Parse {
+            source, templates, ..
+        } => {
+            visitor.visit_parse_source(source);
+            for template in templates {
+                visitor.visit_template(template);
+            }
+        }
+        InstructionKind::Signal(target) => visitor.visit_signal_target(target),
+        InstructionKind::Trace(setting) => visitor.visit_trace_setting(setting),
+    }
+}
+
+pub fn walk_when<V: VisitorMut + ?Sized>(visitor: &mut V, when: &mut When) {
+    visitor.visit_expression(&mut when.condition);
+    visitor.visit_instruction(&mut when.instruction);
+}
+
+pub fn walk_repetitor<V: VisitorMut + ?Sized>(visitor: &mut V, repetitor: &mut Repetitor) {
+    match &mut repetitor.kind {
+        RepetitorKind::Controlled {
+            variable,
+            start,
+            to,
+            by,
+            count,
+        } => {
+            visitor.visit_token(variable);
+            visitor.visit_expression(start);
+            for expression in [to, by, count].into_iter().flatten() {
+                visitor.visit_expression(expression);
+            }
+        }
+        RepetitorKind::Count(expression) => visitor.visit_expression(expression),
+        RepetitorKind::Forever => {}
+    }
+}
+
+pub fn walk_conditional<V: VisitorMut + ?Sized>(visitor: &mut V, conditional: &mut Conditional) {
+    match &mut conditional.kind {
+        ConditionalKind::While(expression) | ConditionalKind::Until(expression) => {
+            visitor.visit_expression(expression);
+        }
+    }
+}
+
+pub fn walk_address_target<V: VisitorMut + ?Sized>(visitor: &mut V, target: &mut AddressTarget) {
+    match &mut target.kind {
+        AddressTargetKind::Swap => {}
+        AddressTargetKind::Environment { name, command } => {
+            visitor.visit_token(name);
+            if let Some(command) = command {
+                visitor.visit_expression(command);
+            }
+        }
+        AddressTargetKind::Value(expression) => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_call_target<V: VisitorMut + ?Sized>(visitor: &mut V, target: &mut CallTarget) {
+    match &mut target.kind {
+        CallTargetKind::Routine(name) => visitor.visit_routine_name(name),
+        CallTargetKind::On { condition, name } => {
+            visitor.visit_token(condition);
+            if let Some(name) = name {
+                visitor.visit_token(name);
+            }
+        }
+        CallTargetKind::Off { condition } => visitor.visit_token(condition),
+    }
+}
+
+pub fn walk_signal_target<V: VisitorMut + ?Sized>(visitor: &mut V, target: &mut SignalTarget) {
+    match &mut target.kind {
+        SignalTargetKind::Label(name) => visitor.visit_token(name),
+        SignalTargetKind::Value(expression) => visitor.visit_expression(expression),
+        SignalTargetKind::On { condition, name } => {
+            visitor.visit_token(condition);
+            if let Some(name) = name {
+                visitor.visit_token(name);
+            }
+        }
+        SignalTargetKind::Off { condition } => visitor.visit_token(condition),
+    }
+}
+
+pub fn walk_routine_name<V: VisitorMut + ?Sized>(visitor: &mut V, name: &mut RoutineName) {
+    match name {
+        RoutineName::Symbol(token) | RoutineName::Literal(token) => visitor.visit_token(token),
+    }
+}
+
+pub fn walk_variable_reference<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    reference: &mut VariableReference,
+) {
+    match &mut reference.kind {
+        VariableReferenceKind::Variable(name) | VariableReferenceKind::Indirect(name) => {
+            visitor.visit_token(name);
+        }
+    }
+}
+
+pub fn walk_numeric_setting<V: VisitorMut + ?Sized>(visitor: &mut V, setting: &mut NumericSetting) {
+    match &mut setting.kind {
+        NumericSettingKind::Digits(expression) | NumericSettingKind::Fuzz(expression) => {
+            if let Some(expression) = expression {
+                visitor.visit_expression(expression);
+            }
+        }
+        NumericSettingKind::Form(form) => {
+            if let Some(form) = form {
+                visitor.visit_numeric_form(form);
+            }
+        }
+    }
+}
+
+pub fn walk_numeric_form<V: VisitorMut + ?Sized>(visitor: &mut V, form: &mut NumericForm) {
+    match &mut form.kind {
+        NumericFormKind::Scientific | NumericFormKind::Engineering => {}
+        NumericFormKind::Value(expression) => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_trace_setting<V: VisitorMut + ?Sized>(visitor: &mut V, setting: &mut TraceSetting) {
+    match &mut setting.kind {
+        TraceSettingKind::Default => {}
+        TraceSettingKind::Setting(token) => visitor.visit_token(token),
+        TraceSettingKind::Value(expression) => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_parse_source<V: VisitorMut + ?Sized>(visitor: &mut V, source: &mut ParseSource) {
+    match &mut source.kind {
+        ParseSourceKind::Value(Some(expression)) => visitor.visit_expression(expression),
+        ParseSourceKind::Var(name) => visitor.visit_token(name),
+        ParseSourceKind::Arg
+        | ParseSourceKind::Linein
+        | ParseSourceKind::Pull
+        | ParseSourceKind::Source
+        | ParseSourceKind::Value(None)
+        | ParseSourceKind::Version => {}
+    }
+}
+
+pub fn walk_template<V: VisitorMut + ?Sized>(visitor: &mut V, template: &mut Template) {
+    for element in &mut template.elements {
+        visitor.visit_template_element(element);
+    }
+}
+
+pub fn walk_template_element<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    element: &mut TemplateElement,
+) {
+    match &mut element.kind {
+        TemplateElementKind::Target(token)
+        | TemplateElementKind::Placeholder(token)
+        | TemplateElementKind::LiteralPattern(token) => visitor.visit_token(token),
+        TemplateElementKind::VariablePattern(expression)
+        | TemplateElementKind::AbsolutePosition(expression)
+        | TemplateElementKind::RelativePosition {
+            offset: expression, ..
+        } => visitor.visit_expression(expression),
+    }
+}
+
+pub fn walk_expression<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut Expression) {
+    match &mut expression.kind {
+        ExpressionKind::Literal(token) | ExpressionKind::Symbol(token) => {
+            visitor.visit_token(token);
+        }
+        ExpressionKind::FunctionCall { name, args } => {
+            visitor.visit_routine_name(name);
+            for arg in args.iter_mut().flatten() {
+                visitor.visit_expression(arg);
+            }
+        }
+        ExpressionKind::Parenthesized(inner) => visitor.visit_expression(inner),
+        ExpressionKind::Prefix { operand, .. } => visitor.visit_expression(operand),
+        ExpressionKind::Binary { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::BinaryOperator;
+    use crate::parser::RexxParser;
+
+    /// Swaps the operands of every `=` comparison.
+    struct SwapEquals;
+
+    impl VisitorMut for SwapEquals {
+        fn visit_expression(&mut self, expression: &mut Expression) {
+            if let ExpressionKind::Binary {
+                operator: BinaryOperator::Equal,
+                left,
+                right,
+            } = &mut expression.kind
+            {
+                std::mem::swap(left, right);
+            }
+            walk_expression(self, expression);
+        }
+    }
+
+    #[test]
+    fn visit_mut_nested_groups() {
+        let source = "select\n  when a = b then do 3\n    say (c = d)\n  end\nend";
+        let mut parser = RexxParser::new(source);
+        let mut program = parser.parse().unwrap();
+        SwapEquals.visit_program(&mut program);
+        // The text of the left operand, inside any parentheses.
+        fn left<'a>(source: &'a str, expression: &Expression) -> &'a str {
+            match &expression.kind {
+                ExpressionKind::Binary { left, .. } => {
+                    &source[left.span.start.index..left.span.end.index]
+                }
+                ExpressionKind::Parenthesized(inner) => left(source, inner),
+                other => panic!("unexpected expression: {other:?}"),
+            }
+        }
+        let InstructionKind::Select { whens, .. } = &program.instructions[0].kind else {
+            panic!("unexpected instructions: {:?}", program.instructions);
+        };
+        assert_eq!(left(source, &whens[0].condition), "b");
+        let InstructionKind::Do { body, .. } = &whens[0].instruction.kind else {
+            panic!("unexpected instruction: {:?}", whens[0].instruction);
+        };
+        let InstructionKind::Say(Some(expression)) = &body[0].kind else {
+            panic!("unexpected instruction: {:?}", body[0]);
+        };
+        assert_eq!(left(source, expression), "d");
+    }
+}
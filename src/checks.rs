@@ -0,0 +1,314 @@
+//! Checks of a parsed program that go beyond the syntax. They find code
+//! that parses but fails when it runs, and report it as warnings.
+
+use std::collections::HashSet;
+
+use crate::ast::visit::{self, Visitor};
+use crate::ast::{
+    CallTarget, CallTargetKind, Instruction, InstructionKind, Program, RoutineName, SignalTarget,
+    SignalTargetKind, Span, Spanned,
+};
+use crate::lexer::{Token, TokenType};
+
+/// Problems found by [`check`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum Warning {
+    /// `SIGNAL label`, or a SIGNAL ON or CALL ON trap, where the program
+    /// has no such label.
+    LabelNotFound(Token),
+    /// A PROCEDURE that is not the first instruction after a label, or that
+    /// the instructions before its label fall through into.
+    UnexpectedProcedure(Span),
+    /// A label that the code after an earlier label runs on into, as that
+    /// routine has no RETURN or EXIT at its end.
+    FallThrough(Token),
+}
+
+/// Checks the program parsed from the source. The warnings are in source
+/// order.
+pub fn check(program: &Program, source: &str) -> Vec<Warning> {
+    let labels = Labels::of(program);
+    let defined = labels
+        .labels
+        .iter()
+        .map(|label| label_name(label, source))
+        .collect::<HashSet<_>>();
+
+    let mut warnings = labels
+        .targets
+        .iter()
+        .filter(|target| !defined.contains(&label_name(target, source)))
+        .cloned()
+        .map(Warning::LabelNotFound)
+        .collect::<Vec<_>>();
+    check_procedures(&program.instructions, &mut warnings);
+    let signalled = labels
+        .targets
+        .iter()
+        .map(|target| label_name(target, source))
+        .collect::<HashSet<_>>();
+    check_fall_through(&program.instructions, &signalled, source, &mut warnings);
+    warnings.sort_by_key(|warning| match warning {
+        Warning::LabelNotFound(token) | Warning::FallThrough(token) => token.range.start.index,
+        Warning::UnexpectedProcedure(span) => span.start.index,
+    });
+    warnings
+}
+
+/// The name that a label or a label reference stands for. Symbols are
+/// uppercased, and strings are taken as they are, without their quotes. A
+/// string without its closing quote is taken with its opening one.
+pub fn label_name(token: &Token, source: &str) -> String {
+    let text = token.text(source);
+    match token.token_type {
+        TokenType::Literal => {
+            let quote = &text[..1];
+            match text[1..].strip_suffix(quote) {
+                Some(content) => content.replace(&quote.repeat(2), quote),
+                None => text.to_string(),
+            }
+        }
+        _ => text.to_ascii_uppercase(),
+    }
+}
+
+/// A PROCEDURE must be the first instruction run by an internal call, so
+/// it must follow a label that the code before cannot fall into. Execution
+/// starts at the top, so a label there is fallen into as well.
+fn check_procedures(instructions: &[Instruction], warnings: &mut Vec<Warning>) {
+    let mut falls_through = true;
+    let mut after_label = false;
+    for instruction in instructions {
+        match &instruction.kind {
+            InstructionKind::Label(_) => {
+                after_label = true;
+                continue;
+            }
+            InstructionKind::Procedure { .. } if falls_through || !after_label => {
+                warnings.push(Warning::UnexpectedProcedure(instruction.span.clone()))
+            }
+            _ => {}
+        }
+        after_label = false;
+        falls_through = !transfers_control(instruction);
+    }
+}
+
+/// The code after a label is taken to be a routine, which must end in
+/// RETURN or EXIT, or it runs on into the next label. Falling into a
+/// label that SIGNAL jumps to is how loops are written with SIGNAL, so
+/// those labels are left alone, as is the code before the first label.
+fn check_fall_through(
+    instructions: &[Instruction],
+    signalled: &HashSet<String>,
+    source: &str,
+    warnings: &mut Vec<Warning>,
+) {
+    let mut in_routine = false;
+    let mut falls_through = false;
+    for instruction in instructions {
+        match &instruction.kind {
+            InstructionKind::Label(name) => {
+                if in_routine && falls_through && !signalled.contains(&label_name(name, source)) {
+                    warnings.push(Warning::FallThrough(name.clone()));
+                }
+                in_routine = true;
+                falls_through = false;
+            }
+            _ => falls_through = !transfers_control(instruction),
+        }
+    }
+}
+
+/// Whether the instruction never lets execution go on to the next one.
+fn transfers_control(instruction: &Instruction) -> bool {
+    matches!(
+        instruction.kind,
+        InstructionKind::Exit(_)
+            | InstructionKind::Return(_)
+            | InstructionKind::Signal(SignalTarget {
+                kind: SignalTargetKind::Label(_) | SignalTargetKind::Value(_),
+                ..
+            })
+    )
+}
+
+/// The labels of a program and the places that refer to them, including
+/// those inside groups.
+#[derive(Debug, Default)]
+pub struct Labels {
+    pub labels: Vec<Token>,
+    /// The labels that SIGNAL and the SIGNAL ON and CALL ON traps go to,
+    /// which must exist. A trap without NAME goes to the label named after
+    /// its condition, so the condition stands for that label.
+    pub targets: Vec<Token>,
+    /// The names of CALL and function calls, which may also name a built-in
+    /// or external routine. A routine named by a string is never an
+    /// internal label, so only symbols are kept.
+    pub calls: Vec<Token>,
+}
+
+impl Labels {
+    pub fn of(program: &Program) -> Labels {
+        let mut labels = Labels::default();
+        labels.visit_program(program);
+        labels
+    }
+
+    /// The targets and the calls, in no particular order.
+    pub fn references(&self) -> impl Iterator<Item = &Token> {
+        self.targets.iter().chain(&self.calls)
+    }
+
+    fn push_trap(&mut self, condition: &Token, name: &Option<Token>) {
+        self.targets
+            .push(name.as_ref().unwrap_or(condition).clone());
+    }
+}
+
+impl Visitor for Labels {
+    fn visit_instruction(&mut self, instruction: &Instruction) {
+        if let InstructionKind::Label(name) = &instruction.kind {
+            self.labels.push(name.clone());
+        }
+        visit::walk_instruction(self, instruction);
+    }
+
+    fn visit_call_target(&mut self, target: &CallTarget) {
+        if let CallTargetKind::On { condition, name } = &target.kind {
+            self.push_trap(condition, name);
+        }
+        visit::walk_call_target(self, target);
+    }
+
+    fn visit_signal_target(&mut self, target: &SignalTarget) {
+        match &target.kind {
+            SignalTargetKind::Label(name) => self.targets.push(name.clone()),
+            SignalTargetKind::On { condition, name } => self.push_trap(condition, name),
+            SignalTargetKind::Value(_) | SignalTargetKind::Off { .. } => {}
+        }
+        visit::walk_signal_target(self, target);
+    }
+
+    fn visit_routine_name(&mut self, name: &RoutineName) {
+        if let RoutineName::Symbol(symbol) = name {
+            self.calls.push(symbol.clone());
+        }
+        visit::walk_routine_name(self, name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::RexxParser;
+
+    fn warnings(source: &str) -> Vec<Warning> {
+        let mut parser = RexxParser::new(source);
+        let (program, _) = parser.parse_recovering();
+        check(&program, source)
+    }
+
+    #[test]
+    fn check_signal_targets() {
+        let source = "signal Done\nif x then signal 'DONE'\nsignal 'done'\nsignal missing\ndone:";
+        let found = warnings(source)
+            .into_iter()
+            .map(|warning| match warning {
+                Warning::LabelNotFound(token) => token.text(source).to_string(),
+                warning => panic!("unexpected warning: {warning:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(found, ["'done'", "missing"]);
+    }
+
+    #[test]
+    fn check_trap_targets() {
+        let source = "signal on error name handler\nsignal on syntax\ncall on halt name 'Stop'\n\
+                      call on failure\ncall on notready name '\nsignal off novalue\nexit\n\
+                      handler: syntax: stop:";
+        let found = warnings(source)
+            .into_iter()
+            .map(|warning| match warning {
+                Warning::LabelNotFound(token) => token.text(source).to_string(),
+                warning => panic!("unexpected warning: {warning:?}"),
+            })
+            .collect::<Vec<_>>();
+        // An unterminated NAME is a label of its own text.
+        assert_eq!(found, ["'Stop'", "failure", "'"]);
+    }
+
+    /// The one-based lines of the misplaced PROCEDURE instructions.
+    fn procedure_lines(source: &str) -> Vec<usize> {
+        warnings(source)
+            .into_iter()
+            .filter_map(|warning| match warning {
+                Warning::UnexpectedProcedure(span) => Some(span.start.line + 1),
+                Warning::FallThrough(_) => None,
+                warning => panic!("unexpected warning: {warning:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn check_procedure_placement() {
+        assert_eq!(
+            procedure_lines(
+                "call a\nexit\na: b:\n  procedure\n  say 'a'\nc: procedure\n  return\n\
+                 d:\n  say 'd'\n  procedure expose x\n  return"
+            ),
+            [6, 10]
+        );
+        assert_eq!(procedure_lines("main: procedure\n  return"), [1]);
+    }
+
+    #[test]
+    fn check_fall_through_labels() {
+        let source = "call a\nexit\na: say 1\nb: say 2\nreturn\nc: d: nop\n\
+                      again: say 3\nif x then signal again\nexit\ne: if x then return\nf:";
+        let found = warnings(source)
+            .into_iter()
+            .map(|warning| match warning {
+                Warning::FallThrough(token) => token.text(source).to_string(),
+                warning => panic!("unexpected warning: {warning:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(found, ["b", "f"]);
+    }
+
+    #[test]
+    fn label_names() {
+        let source = "Loop: 'it''s' \"a\"";
+        let mut lexer = crate::Lexer::new(source);
+        let names = lexer.tokenize()[0]
+            .tokens
+            .iter()
+            .filter(|token| matches!(token.token_type, TokenType::Symbol(_) | TokenType::Literal))
+            .map(|token| label_name(token, source))
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["LOOP", "it's", "a"]);
+    }
+
+    #[test]
+    fn unterminated_label_names() {
+        for source in ["signal '", "signal 'done", "signal \"a\"\"", "signal ''"] {
+            let found = warnings(source)
+                .into_iter()
+                .map(|warning| match warning {
+                    Warning::LabelNotFound(token) => label_name(&token, source),
+                    warning => panic!("unexpected warning: {warning:?}"),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(found.len(), 1, "{source}");
+        }
+        let source = "signal 'done";
+        let mut lexer = crate::Lexer::new(source);
+        let names = lexer.tokenize()[0]
+            .tokens
+            .iter()
+            .filter(|token| token.token_type == TokenType::Literal)
+            .map(|token| label_name(token, source))
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["'done"]);
+    }
+}
@@ -0,0 +1,564 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::Spanned;
+use crate::checks::Warning;
+use crate::lexer::{LexError, Range, SymbolKind, Token, TokenType};
+use crate::parser::ParseError;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A standard error number and subcode, such as 6.1. A subcode of 0 means
+/// the error has no subcodes, as with 36.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct ErrorCode {
+    pub number: u8,
+    pub subcode: u8,
+}
+
+impl ErrorCode {
+    const fn new(number: u8, subcode: u8) -> ErrorCode {
+        ErrorCode { number, subcode }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.subcode {
+            0 => write!(f, "{}", self.number),
+            subcode => write!(f, "{}.{}", self.number, subcode),
+        }
+    }
+}
+
+/// A problem in the source, with the standard message text
+/// and its inserts filled in. Only warnings about code that runs without
+/// raising an error have no code.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub code: Option<ErrorCode>,
+    pub severity: Severity,
+    pub message: String,
+    pub range: Range,
+}
+
+/// Shown as `line:column: severity [code] message`, with one-based lines and
+/// columns. `[code]` is left out when there is none.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} ",
+            self.range.start.line + 1,
+            self.range.start.character + 1,
+            self.severity,
+        )?;
+        if let Some(code) = self.code {
+            write!(f, "[{code}] ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Diagnostic {
+    fn error(code: ErrorCode, message: String, range: Range) -> Diagnostic {
+        Diagnostic {
+            code: Some(code),
+            severity: Severity::Error,
+            message,
+            range,
+        }
+    }
+
+    /// Builds the diagnostic for a warning of the checks. It has the code
+    /// of the error that the instruction raises when it runs, if any.
+    pub fn from_warning(warning: &Warning, source: &str) -> Diagnostic {
+        let (code, message, range) = match warning {
+            Warning::LabelNotFound(token) => (
+                Some(ErrorCode::new(16, 1)),
+                format!("Label \"{}\" not found", token.text(source)),
+                token.range.clone(),
+            ),
+            Warning::UnexpectedProcedure(span) => (
+                Some(ErrorCode::new(17, 1)),
+                "PROCEDURE is valid only when it is the first instruction executed after an \
+                 internal CALL or function invocation"
+                    .to_string(),
+                span.clone(),
+            ),
+            Warning::FallThrough(label) => (
+                None,
+                format!(
+                    "The code before label \"{}\" runs on into it without RETURN or EXIT",
+                    label.text(source)
+                ),
+                label.range.clone(),
+            ),
+        };
+        Diagnostic {
+            code,
+            severity: Severity::Warning,
+            message,
+            range,
+        }
+    }
+
+    pub fn from_lex_error(error: &LexError) -> Diagnostic {
+        let radix = |binary: bool| if binary { "binary" } else { "hexadecimal" };
+        match error {
+            LexError::UnterminatedComment { range } => Diagnostic::error(
+                ErrorCode::new(6, 1),
+                "Unmatched comment delimiter (\"/*\")".to_string(),
+                range.clone(),
+            ),
+            LexError::UnterminatedString { quote, range } => {
+                let (subcode, name) = if *quote == '\'' {
+                    (2, "single")
+                } else {
+                    (3, "double")
+                };
+                Diagnostic::error(
+                    ErrorCode::new(6, subcode),
+                    format!("Unmatched {name} quote ({quote})"),
+                    range.clone(),
+                )
+            }
+            LexError::InvalidBlank {
+                binary,
+                position,
+                range,
+            } => Diagnostic::error(
+                ErrorCode::new(15, if *binary { 2 } else { 1 }),
+                format!(
+                    "Invalid location of blank in position {position} in {} string",
+                    radix(*binary)
+                ),
+                range.clone(),
+            ),
+            LexError::InvalidDigit {
+                binary,
+                found,
+                range,
+            } => {
+                let (subcode, valid) = if *binary {
+                    (4, "0, 1")
+                } else {
+                    (3, "0-9, a-f, A-F")
+                };
+                Diagnostic::error(
+                    ErrorCode::new(15, subcode),
+                    format!(
+                        "Only {valid}, and blank are valid in a {} string; found \"{found}\"",
+                        radix(*binary)
+                    ),
+                    range.clone(),
+                )
+            }
+            LexError::InvalidCharacter { found, range } => {
+                let mut buffer = [0; 4];
+                let hex = found
+                    .encode_utf8(&mut buffer)
+                    .bytes()
+                    .map(|byte| format!("{byte:02X}"))
+                    .collect::<String>();
+                Diagnostic::error(
+                    ErrorCode::new(13, 1),
+                    format!("Invalid character in program \"('{hex}'X)\""),
+                    range.clone(),
+                )
+            }
+        }
+    }
+
+    /// Builds the diagnostic for a parse error. The source is needed for the
+    /// text of the tokens inserted into the message.
+    pub fn from_parse_error(error: &ParseError, source: &str) -> Diagnostic {
+        let text = |token: &Token| &source[token.range.start.index..token.range.end.index];
+        // The `; found "<token>"` part of a message, left out when the clause
+        // simply ended.
+        let found_insert = |token: &Token| match token.token_type {
+            TokenType::EOL | TokenType::Semicolon | TokenType::EOS => String::new(),
+            _ => format!("; found \"{}\"", text(token)),
+        };
+        let line = |token: &Token| token.range.start.line + 1;
+
+        let (code, message, token) = match error {
+            ParseError::InvalidExpression(token) => (
+                ErrorCode::new(35, 1),
+                format!("Invalid expression detected at \"{}\"", text(token)),
+                token,
+            ),
+            ParseError::UnmatchedParenthesis(token) => (
+                ErrorCode::new(36, 0),
+                "Unmatched \"(\" in expression".to_string(),
+                token,
+            ),
+            ParseError::UnexpectedComma(token) => {
+                (ErrorCode::new(37, 1), "Unexpected \",\"".to_string(), token)
+            }
+            ParseError::UnexpectedParenthesis(token) => (
+                ErrorCode::new(37, 2),
+                "Unmatched \")\" in expression".to_string(),
+                token,
+            ),
+            ParseError::InvalidAssignmentTarget(token) => {
+                let name = text(token);
+                let (subcode, message) =
+                    if token.token_type == TokenType::Symbol(SymbolKind::Number) {
+                        (1, "Values cannot be assigned to a number")
+                    } else if name.starts_with('.') {
+                        (3, "Variable symbol must not start with a \".\"")
+                    } else {
+                        (2, "Variable symbol must not start with a number")
+                    };
+                (
+                    ErrorCode::new(31, subcode),
+                    format!("{message}; found \"{name}\""),
+                    token,
+                )
+            }
+            ParseError::UnmatchedEnd(token) => (
+                ErrorCode::new(10, 1),
+                "END has no corresponding DO or SELECT".to_string(),
+                token,
+            ),
+            ParseError::EndNameMismatch { keyword, found } => (
+                ErrorCode::new(10, 2),
+                format!(
+                    "END corresponding to DO on line {} must have a symbol following that \
+                     matches the control variable (or no symbol); found \"{}\"",
+                    line(keyword),
+                    text(found)
+                ),
+                found,
+            ),
+            ParseError::UnexpectedEndName { keyword, found } => (
+                ErrorCode::new(10, 3),
+                format!(
+                    "END corresponding to DO on line {} must not have a symbol following it \
+                     because there is no control variable; found \"{}\"",
+                    line(keyword),
+                    text(found)
+                ),
+                found,
+            ),
+            ParseError::IncompleteDo(token) => (
+                ErrorCode::new(14, 1),
+                "DO instruction requires a matching END".to_string(),
+                token,
+            ),
+            ParseError::InvalidDoKeyword(token) => (
+                ErrorCode::new(27, 1),
+                format!("Invalid use of keyword \"{}\" in DO clause", text(token)),
+                token,
+            ),
+            ParseError::UnexpectedThen(token) => (
+                ErrorCode::new(8, 1),
+                "THEN has no corresponding IF or WHEN clause".to_string(),
+                token,
+            ),
+            ParseError::UnexpectedElse(token) => (
+                ErrorCode::new(8, 2),
+                "ELSE has no corresponding THEN clause".to_string(),
+                token,
+            ),
+            ParseError::UnexpectedWhen(token) => (
+                ErrorCode::new(9, 1),
+                "WHEN has no corresponding SELECT".to_string(),
+                token,
+            ),
+            ParseError::UnexpectedOtherwise(token) => (
+                ErrorCode::new(9, 2),
+                "OTHERWISE has no corresponding SELECT".to_string(),
+                token,
+            ),
+            ParseError::MissingThen { keyword, found } => {
+                let name = text(keyword).to_uppercase();
+                (
+                    ErrorCode::new(18, if name == "WHEN" { 2 } else { 1 }),
+                    format!(
+                        "{name} on line {} requires matching THEN clause{}",
+                        line(keyword),
+                        found_insert(found)
+                    ),
+                    found,
+                )
+            }
+            ParseError::IncompleteSelect(token) => (
+                ErrorCode::new(14, 2),
+                "SELECT instruction requires a matching END".to_string(),
+                token,
+            ),
+            ParseError::IncompleteThen(token) => (
+                ErrorCode::new(14, 3),
+                "THEN requires a following instruction".to_string(),
+                token,
+            ),
+            ParseError::IncompleteElse(token) => (
+                ErrorCode::new(14, 4),
+                "ELSE requires a following instruction".to_string(),
+                token,
+            ),
+            ParseError::EndAfterThen(token) => (
+                ErrorCode::new(10, 5),
+                "END must not immediately follow THEN".to_string(),
+                token,
+            ),
+            ParseError::EndAfterElse(token) => (
+                ErrorCode::new(10, 6),
+                "END must not immediately follow ELSE".to_string(),
+                token,
+            ),
+            ParseError::MissingWhen { keyword, found } => (
+                ErrorCode::new(7, 1),
+                format!(
+                    "SELECT on line {} requires WHEN{}",
+                    line(keyword),
+                    found_insert(found)
+                ),
+                found,
+            ),
+            ParseError::InvalidSelectClause { keyword, found } => (
+                ErrorCode::new(7, 2),
+                format!(
+                    "SELECT on line {} requires WHEN, OTHERWISE, or END{}",
+                    line(keyword),
+                    found_insert(found)
+                ),
+                found,
+            ),
+            ParseError::SelectEndName { keyword, found } => (
+                ErrorCode::new(10, 4),
+                format!(
+                    "END corresponding to SELECT on line {} must not have a symbol following; \
+                     found \"{}\"",
+                    line(keyword),
+                    text(found)
+                ),
+                found,
+            ),
+            ParseError::InvalidParseKeyword(token) => (
+                ErrorCode::new(25, 12),
+                format!(
+                    "PARSE must be followed by one of the keywords ARG, LINEIN, PULL, SOURCE, \
+                     VALUE, VAR, or VERSION{}",
+                    found_insert(token)
+                ),
+                token,
+            ),
+            ParseError::ExpectedSymbol { keyword, found } => (
+                ErrorCode::new(20, 1),
+                format!(
+                    "Symbol expected after {}{}",
+                    text(keyword).to_uppercase(),
+                    found_insert(found)
+                ),
+                found,
+            ),
+            ParseError::InvalidTemplate(token) => (
+                ErrorCode::new(38, 1),
+                format!("Invalid parsing template detected at \"{}\"", text(token)),
+                token,
+            ),
+            ParseError::InvalidPosition(token) => (
+                ErrorCode::new(38, 2),
+                format!("Invalid parsing position detected at \"{}\"", text(token)),
+                token,
+            ),
+            ParseError::MissingWith(token) => (
+                ErrorCode::new(38, 3),
+                "PARSE VALUE instruction requires WITH keyword".to_string(),
+                token,
+            ),
+            ParseError::UnexpectedToken(token) => (
+                ErrorCode::new(21, 1),
+                format!(
+                    "The clause ended at an unexpected token{}",
+                    found_insert(token)
+                ),
+                token,
+            ),
+            ParseError::ExpectedRoutineName(token) => (
+                ErrorCode::new(19, 2),
+                format!(
+                    "String or symbol expected after CALL{}",
+                    found_insert(token)
+                ),
+                token,
+            ),
+            ParseError::ExpectedTrapName(token) => (
+                ErrorCode::new(19, 3),
+                format!(
+                    "String or symbol expected after NAME{}",
+                    found_insert(token)
+                ),
+                token,
+            ),
+            ParseError::InvalidCallCondition { keyword, found } => {
+                let on = text(keyword).eq_ignore_ascii_case("ON");
+                (
+                    ErrorCode::new(25, if on { 1 } else { 2 }),
+                    format!(
+                        "CALL {} must be followed by one of the keywords ERROR, FAILURE, HALT, \
+                         or NOTREADY{}",
+                        if on { "ON" } else { "OFF" },
+                        found_insert(found)
+                    ),
+                    found,
+                )
+            }
+            ParseError::ExpectedLabelName(token) => (
+                ErrorCode::new(19, 4),
+                format!(
+                    "String or symbol expected after SIGNAL{}",
+                    found_insert(token)
+                ),
+                token,
+            ),
+            ParseError::InvalidSignalCondition { keyword, found } => {
+                let on = text(keyword).eq_ignore_ascii_case("ON");
+                (
+                    ErrorCode::new(25, if on { 3 } else { 4 }),
+                    format!(
+                        "SIGNAL {} must be followed by one of the keywords ERROR, FAILURE, \
+                         HALT, LOSTDIGITS, NOTREADY, NOVALUE, or SYNTAX{}",
+                        if on { "ON" } else { "OFF" },
+                        found_insert(found)
+                    ),
+                    found,
+                )
+            }
+            ParseError::InvalidVariableReference(token) => (
+                ErrorCode::new(46, 1),
+                format!(
+                    "Extra token \"{}\" found in variable reference; \")\" expected",
+                    text(token)
+                ),
+                token,
+            ),
+            ParseError::InvalidNumericKeyword(token) => (
+                ErrorCode::new(25, 15),
+                format!(
+                    "NUMERIC must be followed by one of the keywords DIGITS, FORM, or FUZZ{}",
+                    found_insert(token)
+                ),
+                token,
+            ),
+            ParseError::InvalidNumericForm(token) => (
+                ErrorCode::new(25, 11),
+                format!(
+                    "NUMERIC FORM must be followed by one of the keywords ENGINEERING or \
+                     SCIENTIFIC{}",
+                    found_insert(token)
+                ),
+                token,
+            ),
+            ParseError::InvalidProcedureKeyword(token) => (
+                ErrorCode::new(25, 17),
+                format!(
+                    "PROCEDURE must be followed by the keyword EXPOSE or nothing{}",
+                    found_insert(token)
+                ),
+                token,
+            ),
+            ParseError::InvalidTraceSetting(token) => (
+                ErrorCode::new(24, 1),
+                format!(
+                    "TRACE request letter must be one of \"ACEFILNOR\"; found \"{}\"",
+                    text(token)
+                ),
+                token,
+            ),
+        };
+        Diagnostic::error(code, message, token.range.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::RexxParser;
+
+    fn lex_diagnostics(source: &str) -> Vec<String> {
+        let mut lexer = Lexer::new(source);
+        lexer.tokenize();
+        lexer
+            .diagnostics()
+            .iter()
+            .map(|error| Diagnostic::from_lex_error(error).to_string())
+            .collect()
+    }
+
+    fn parse_diagnostic(source: &str) -> String {
+        let mut parser = RexxParser::new(source);
+        let error = parser.parse().unwrap_err();
+        Diagnostic::from_parse_error(&error, source).to_string()
+    }
+
+    #[test]
+    fn lex_error_messages() {
+        assert_eq!(
+            lex_diagnostics("say 1\n/* open"),
+            ["2:1: error [6.1] Unmatched comment delimiter (\"/*\")"]
+        );
+        assert_eq!(
+            lex_diagnostics("say \"abc"),
+            ["1:5: error [6.3] Unmatched double quote (\")"]
+        );
+        assert_eq!(
+            lex_diagnostics("x = 1 ~ 2"),
+            ["1:7: error [13.1] Invalid character in program \"('7E'X)\""]
+        );
+        assert_eq!(
+            lex_diagnostics("x = '1 234'x '2'b"),
+            [
+                "1:5: error [15.1] Invalid location of blank in position 2 in hexadecimal string",
+                "1:14: error [15.4] Only 0, 1, and blank are valid in a binary string; found \"2\"",
+            ]
+        );
+        assert_eq!(
+            lex_diagnostics("say 'é'\r\nsay 'ü'; ~"),
+            ["2:10: error [13.1] Invalid character in program \"('7E'X)\""]
+        );
+    }
+
+    #[test]
+    fn parse_error_messages() {
+        assert_eq!(
+            parse_diagnostic("x = (1 + 2"),
+            "1:5: error [36] Unmatched \"(\" in expression"
+        );
+        assert_eq!(
+            parse_diagnostic("if a\n  say b"),
+            "1:5: error [18.1] IF on line 1 requires matching THEN clause"
+        );
+        assert_eq!(
+            parse_diagnostic("3x = 1"),
+            "1:1: error [31.2] Variable symbol must not start with a number; found \"3x\""
+        );
+        assert_eq!(
+            parse_diagnostic("drop"),
+            "1:5: error [20.1] Symbol expected after DROP"
+        );
+        assert_eq!(
+            parse_diagnostic("signal off novalue; call off novalue"),
+            "1:30: error [25.2] CALL OFF must be followed by one of the keywords ERROR, \
+             FAILURE, HALT, or NOTREADY; found \"novalue\""
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::lexer::types::{Range, Token, TokenType};
+use crate::lexer::types::{LexError, Range, SymbolKind, Token, TokenType};
 
 use super::{types::LogicalLine, Position};
 
@@ -6,6 +6,7 @@ pub struct Lexer<'a> {
     line_counter: usize,
     line_start_index: usize,
     source: &'a str,
+    diagnostics: Vec<LexError>,
 }
 
 impl<'a> Lexer<'a> {
@@ -14,14 +15,27 @@ impl<'a> Lexer<'a> {
             source: src,
             line_counter: 0,
             line_start_index: 0,
+            diagnostics: Vec::new(),
         }
     }
 
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
     pub fn get_text(&self, token: &Token) -> &str {
         &self.source[token.range.start.index..token.range.end.index]
     }
 
+    /// The errors found by the last call to `tokenize`.
+    pub fn diagnostics(&self) -> &[LexError] {
+        &self.diagnostics
+    }
+
     pub fn tokenize(&mut self) -> Vec<LogicalLine> {
+        self.line_counter = 0;
+        self.line_start_index = 0;
+        self.diagnostics.clear();
         let mut logical_lines = Vec::new();
         let mut line = LogicalLine { tokens: Vec::new() };
 
@@ -31,32 +45,28 @@ impl<'a> Lexer<'a> {
             let token = match ch {
                 ' ' | '\t' => self.consume_whitespaces(&mut chars, pos, &ch),
                 '\n' | '\r' => {
+                    // `\r\n` is a single line end.
+                    let mut end = pos + 1;
+                    if ch == '\r' && chars.next_if(|&(_, next)| next == '\n').is_some() {
+                        end += 1;
+                    }
                     let after_comma = line.tokens.last().is_some()
                         && line.tokens.last().unwrap().token_type == TokenType::Comma;
                     let before_eos = chars.peek().is_none();
-                    eol = if !after_comma {
-                        true
-                    } else if !before_eos {
-                        false
-                    } else {
-                        true
-                    };
-                    let line_token =Token {
+                    eol = !after_comma || before_eos;
+                    let line_token = Token {
                         token_type: TokenType::EOL,
-                        range: self.make_one_line_range(pos, pos + 1),
+                        range: self.make_one_line_range(pos, end),
                     };
                     self.line_counter += 1;
-                    self.line_start_index = pos + 1;
+                    self.line_start_index = end;
                     line_token
                 }
                 '/' => {
                     if let Some((_, '*')) = chars.peek() {
                         self.consume_block_comment(&mut chars, pos)
                     } else {
-                        Token {
-                            token_type: TokenType::Todo,
-                            range: self.make_one_line_range(pos, pos + 1),
-                        }
+                        self.consume_operator(&mut chars, pos, ch)
                     }
                 }
                 '"' | '\'' => self.consume_string_literal(&mut chars, pos, ch),
@@ -68,15 +78,17 @@ impl<'a> Lexer<'a> {
                     token_type: TokenType::Colon,
                     range: self.make_one_line_range(pos, pos + 1),
                 },
-                // TODO: arephmetics
-                '+' | '-' | '*' | '(' | ')' => Token {
-                    token_type: TokenType::Todo,
+                '(' => Token {
+                    token_type: TokenType::LeftParen,
                     range: self.make_one_line_range(pos, pos + 1),
                 },
-                '=' => Token {
-                    token_type: TokenType::Semicolon,
+                ')' => Token {
+                    token_type: TokenType::RightParen,
                     range: self.make_one_line_range(pos, pos + 1),
                 },
+                '+' | '-' | '*' | '%' | '|' | '&' | '=' | '<' | '>' | '\\' | '¬' | '^' => {
+                    self.consume_operator(&mut chars, pos, ch)
+                }
                 ';' => {
                     eol = true;
                     Token {
@@ -85,13 +97,19 @@ impl<'a> Lexer<'a> {
                     }
                 }
 
-                c if c.is_ascii_alphabetic() => self.consume_identifier(&mut chars, pos),
-                c if c.is_ascii_digit() => self.consume_number(&mut chars, pos),
+                c if is_symbol_char(c) => self.consume_symbol(&mut chars, pos),
 
-                _ => Token {
-                    token_type: TokenType::Unknown,
-                    range: self.make_one_line_range(pos, pos + 1),
-                },
+                c => {
+                    let range = self.make_one_line_range(pos, pos + c.len_utf8());
+                    self.diagnostics.push(LexError::InvalidCharacter {
+                        found: c,
+                        range: range.clone(),
+                    });
+                    Token {
+                        token_type: TokenType::Unknown,
+                        range,
+                    }
+                }
             };
             line.tokens.push(token);
 
@@ -108,63 +126,176 @@ impl<'a> Lexer<'a> {
         logical_lines
     }
 
-    fn consume_identifier(
+    /// Consumes a symbol and classifies it. A sign directly after the `E` of
+    /// a number's exponent belongs to the symbol, so `1.5E+3` is one token.
+    fn consume_symbol(
         &self,
         chars: &mut std::iter::Peekable<std::str::CharIndices>,
         start: usize,
     ) -> Token {
         let mut end = start + 1;
-        while let Some((_, ch)) = chars.peek() {
-            if !ch.is_ascii_alphanumeric() && *ch != '_' {
-                break;
+        loop {
+            while let Some(&(pos, ch)) = chars.peek() {
+                if !is_symbol_char(ch) {
+                    break;
+                }
+                end = pos + 1;
+                chars.next();
+            }
+            let mut lookahead = chars.clone();
+            match (lookahead.next(), lookahead.peek()) {
+                (Some((pos, '+' | '-')), Some((_, digit)))
+                    if digit.is_ascii_digit() && is_exponent_start(&self.source[start..end]) =>
+                {
+                    chars.next();
+                    end = pos + 1;
+                }
+                _ => break,
             }
-            end += 1;
-            chars.next();
         }
+        let text = &self.source[start..end];
         Token {
-            token_type: TokenType::Identifier,
+            token_type: TokenType::Symbol(self.classify_symbol(text, start)),
             range: self.make_one_line_range(start, end),
         }
     }
 
-    fn consume_number(
-        &self,
+    fn classify_symbol(&self, text: &str, start: usize) -> SymbolKind {
+        if text.starts_with(|ch: char| ch.is_ascii_digit() || ch == '.') {
+            return if is_number(text) {
+                SymbolKind::Number
+            } else {
+                SymbolKind::Constant
+            };
+        }
+        let Some(stem_end) = text.find('.') else {
+            return SymbolKind::Simple;
+        };
+        if stem_end == text.len() - 1 {
+            return SymbolKind::Stem;
+        }
+        let mut tail = Vec::new();
+        let mut part_start = start + stem_end + 1;
+        for part in text[stem_end + 1..].split('.') {
+            tail.push(self.make_one_line_range(part_start, part_start + part.len()));
+            part_start += part.len() + 1;
+        }
+        SymbolKind::Compound { tail }
+    }
+
+    /// Consumes a string literal. A quote inside the string is written as two
+    /// quotes, and a trailing `X` or `B` turns it into a hexadecimal or binary
+    /// string. Strings cannot span lines, so an unterminated string stops at
+    /// the end of the line.
+    fn consume_string_literal(
+        &mut self,
         chars: &mut std::iter::Peekable<std::str::CharIndices>,
         start: usize,
+        quote: char,
     ) -> Token {
         let mut end = start + 1;
-        while let Some((_, ch)) = chars.peek() {
-            if !ch.is_ascii_digit() {
+        let mut terminated = false;
+        while let Some(&(pos, ch)) = chars.peek() {
+            if ch == '\n' || ch == '\r' {
                 break;
             }
-            end += 1;
             chars.next();
+            end = pos + ch.len_utf8();
+            if ch == quote {
+                if let Some((_, next)) = chars.peek() {
+                    if *next == quote {
+                        chars.next();
+                        end += 1;
+                        continue;
+                    }
+                }
+                terminated = true;
+                break;
+            }
         }
-        Token {
-            token_type: TokenType::Number,
-            range: self.make_one_line_range(start, end),
+        if !terminated {
+            let range = self.make_one_line_range(start, end);
+            self.diagnostics.push(LexError::UnterminatedString {
+                quote,
+                range: range.clone(),
+            });
+            return Token {
+                token_type: TokenType::Literal,
+                range,
+            };
         }
+
+        // The radix suffix only applies when it is not the start of a longer symbol.
+        let mut lookahead = chars.clone();
+        let suffix = match lookahead.next() {
+            Some((_, ch @ ('x' | 'X' | 'b' | 'B')))
+                if !lookahead
+                    .peek()
+                    .is_some_and(|(_, next)| is_symbol_char(*next)) =>
+            {
+                Some(ch.to_ascii_uppercase())
+            }
+            _ => None,
+        };
+        let content = &self.source[start + 1..end - 1];
+        let (token_type, error) = match suffix {
+            Some(suffix) => {
+                chars.next();
+                end += 1;
+                let binary = suffix == 'B';
+                let range = self.make_one_line_range(start, end);
+                let token_type = if binary {
+                    TokenType::BinaryString
+                } else {
+                    TokenType::HexString
+                };
+                (token_type, check_radix_string(content, binary, &range))
+            }
+            None => (TokenType::Literal, None),
+        };
+        self.diagnostics.extend(error);
+        let range = self.make_one_line_range(start, end);
+        Token { token_type, range }
     }
 
-    fn consume_string_literal(
+    /// Consumes an operator, taking the longest sequence of characters that
+    /// still forms a valid REXX operator. Not signs (`\`, `¬`, `^`) are
+    /// interchangeable, and blanks may appear between the characters of a
+    /// comparison operator (`> =` is the same as `>=`).
+    fn consume_operator(
         &self,
         chars: &mut std::iter::Peekable<std::str::CharIndices>,
         start: usize,
-        first_quote: char,
+        first: char,
     ) -> Token {
-        let mut end = start + 1;
-        while let Some((_, ch)) = chars.next() {
-            end += 1;
-            if ch == first_quote {
+        let mut operator = String::from(normalize_not_sign(first));
+        let mut end = start + first.len_utf8();
+        loop {
+            let mut lookahead = chars.clone();
+            if operator.chars().all(is_comparison_char) {
+                while let Some((_, ' ' | '\t')) = lookahead.peek() {
+                    lookahead.next();
+                }
+            }
+            let Some(&(pos, ch)) = lookahead.peek() else {
+                break;
+            };
+            let mut candidate = operator.clone();
+            candidate.push(normalize_not_sign(ch));
+            if operator_type(&candidate).is_none() {
+                break;
+            }
+            // Only comparison operators may have blanks between their characters.
+            if pos != end && !is_comparison_char(ch) {
                 break;
-            } else if ch == '\\' {
-                // TODO: diagnostics on unsupported escape sequences
-                chars.next();
-                end += 1;
             }
+            lookahead.next();
+            *chars = lookahead;
+            operator = candidate;
+            end = pos + ch.len_utf8();
         }
         Token {
-            token_type: TokenType::Literal,
+            token_type: operator_type(&operator).unwrap_or(TokenType::Unknown),
             range: self.make_one_line_range(start, end),
         }
     }
@@ -183,7 +314,7 @@ impl<'a> Lexer<'a> {
             end += 1;
             chars.next();
         }
-                Token {
+        Token {
             token_type: TokenType::Whitespace,
             range: self.make_one_line_range(start, end),
         }
@@ -197,32 +328,47 @@ impl<'a> Lexer<'a> {
         let start_pos = self.make_position(start);
         let mut depth = 0;
         let mut end = start + 1;
+        let mut terminated = false;
         chars.next(); // Consume '*' after '/'
         end += 1;
         while let Some((pos, ch)) = chars.next() {
-            end += 1;
-            if ch == '\n' || ch == '\r' {
+            end = pos + ch.len_utf8();
+            let crlf = ch == '\r' && matches!(chars.peek(), Some((_, '\n')));
+            if ch == '\n' || (ch == '\r' && !crlf) {
                 self.line_counter += 1;
                 self.line_start_index = pos + 1;
             }
             if ch == '*' {
-                if let Some((_, '/')) = chars.peek() {
+                if let Some(&(close, '/')) = chars.peek() {
                     chars.next();
-                    end += 1;
+                    end = close + 1;
                     if depth == 0 {
+                        terminated = true;
                         break;
                     }
                     depth -= 1;
                 }
             }
             if ch == '/' {
-                if let Some((_, '*')) = chars.peek() {
+                if let Some(&(open, '*')) = chars.peek() {
                     chars.next();
-                    end += 1;
+                    end = open + 1;
                     depth += 1;
                 }
             }
         }
+        if !terminated {
+            // Reported at the opening delimiter, which is always on one line.
+            let mut delimiter_end = start_pos.clone();
+            delimiter_end.character += 2;
+            delimiter_end.index += 2;
+            self.diagnostics.push(LexError::UnterminatedComment {
+                range: Range {
+                    start: start_pos.clone(),
+                    end: delimiter_end,
+                },
+            });
+        }
         let range = Range {
             start: start_pos,
             end: self.make_position(end),
@@ -236,7 +382,7 @@ impl<'a> Lexer<'a> {
     fn make_position(&self, index: usize) -> Position {
         Position {
             line: self.line_counter,
-            character: index - self.line_start_index,
+            character: self.source[self.line_start_index..index].chars().count(),
             index,
         }
     }
@@ -248,7 +394,153 @@ impl<'a> Lexer<'a> {
     }
 }
 
+fn is_symbol_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || matches!(ch, '.' | '!' | '?' | '_' | '@' | '#' | '$')
+}
+
+/// Whether `text` is the mantissa of a number followed by the `E` of an
+/// exponent, so that a following sign belongs to the symbol. A number has
+/// at most one exponent.
+fn is_exponent_start(text: &str) -> bool {
+    match text.strip_suffix(['e', 'E']) {
+        Some(mantissa) => !mantissa.contains(['e', 'E']) && is_number(mantissa),
+        None => false,
+    }
+}
+
+/// Whether a constant symbol is a number: digits with an optional decimal
+/// point and an optional signed exponent.
+fn is_number(text: &str) -> bool {
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(i) => (&text[..i], Some(&text[i + 1..])),
+        None => (text, None),
+    };
+    let mut parts = mantissa.splitn(2, '.');
+    let integer = parts.next().unwrap_or_default();
+    let fraction = parts.next().unwrap_or_default();
+    let digits = |s: &str| s.chars().all(|ch| ch.is_ascii_digit());
+    let valid_mantissa =
+        digits(integer) && digits(fraction) && !(integer.is_empty() && fraction.is_empty());
+    let valid_exponent = match exponent {
+        Some(exponent) => {
+            let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            !exponent.is_empty() && digits(exponent)
+        }
+        None => true,
+    };
+    valid_mantissa && valid_exponent
+}
+
+/// Checks the digits and grouping of a hexadecimal or binary string. Blanks
+/// may only separate groups, and every group but the first must hold whole
+/// bytes (two hexadecimal digits) or nibbles (four binary digits).
+fn check_radix_string(content: &str, binary: bool, range: &Range) -> Option<LexError> {
+    let group_size = if binary { 4 } else { 2 };
+    let chars = content.chars().collect::<Vec<_>>();
+    let is_blank = |ch: char| ch == ' ' || ch == '\t';
+    let invalid_blank = |position| LexError::InvalidBlank {
+        binary,
+        position,
+        range: range.clone(),
+    };
+
+    let valid_digit = |ch: char| {
+        if binary {
+            ch == '0' || ch == '1'
+        } else {
+            ch.is_ascii_hexdigit()
+        }
+    };
+    if let Some(found) = chars
+        .iter()
+        .find(|ch| !is_blank(**ch) && !valid_digit(**ch))
+    {
+        return Some(LexError::InvalidDigit {
+            binary,
+            found: *found,
+            range: range.clone(),
+        });
+    }
+    if chars.first().is_some_and(|ch| is_blank(*ch)) {
+        return Some(invalid_blank(1));
+    }
+    if chars.last().is_some_and(|ch| is_blank(*ch)) {
+        return Some(invalid_blank(chars.len()));
+    }
+
+    // Blank positions are one-based, as in the standard's messages.
+    let mut group_len = 0;
+    let mut previous_blank = None;
+    for (i, ch) in chars.iter().enumerate() {
+        if !is_blank(*ch) {
+            group_len += 1;
+            continue;
+        }
+        if group_len > 0 {
+            if let Some(position) = previous_blank {
+                if group_len % group_size != 0 {
+                    return Some(invalid_blank(position));
+                }
+            }
+            previous_blank = Some(i + 1);
+        }
+        group_len = 0;
+    }
+    match previous_blank {
+        Some(position) if group_len % group_size != 0 => Some(invalid_blank(position)),
+        _ => None,
+    }
+}
+
+fn normalize_not_sign(ch: char) -> char {
+    match ch {
+        '¬' | '^' => '\\',
+        _ => ch,
+    }
+}
+
+fn is_comparison_char(ch: char) -> bool {
+    matches!(ch, '=' | '<' | '>' | '\\' | '¬' | '^')
+}
+
+/// Maps an operator (with not signs normalized to `\`) to its token type.
+fn operator_type(operator: &str) -> Option<TokenType> {
+    let token_type = match operator {
+        "+" => TokenType::Plus,
+        "-" => TokenType::Minus,
+        "*" => TokenType::Multiply,
+        "**" => TokenType::Power,
+        "/" => TokenType::Divide,
+        "%" => TokenType::IntegerDivide,
+        "//" => TokenType::Remainder,
+        "||" => TokenType::Concatenate,
+        "\\" => TokenType::Not,
+        "&" => TokenType::And,
+        "|" => TokenType::Or,
+        "&&" => TokenType::Xor,
+        "=" => TokenType::Equal,
+        "\\=" | "<>" | "><" => TokenType::NotEqual,
+        ">" => TokenType::Greater,
+        "<" => TokenType::Less,
+        ">=" => TokenType::GreaterOrEqual,
+        "<=" => TokenType::LessOrEqual,
+        "\\>" => TokenType::NotGreater,
+        "\\<" => TokenType::NotLess,
+        "==" => TokenType::StrictEqual,
+        "\\==" => TokenType::StrictNotEqual,
+        ">>" => TokenType::StrictGreater,
+        "<<" => TokenType::StrictLess,
+        ">>=" => TokenType::StrictGreaterOrEqual,
+        "<<=" => TokenType::StrictLessOrEqual,
+        "\\>>" => TokenType::StrictNotGreater,
+        "\\<<" => TokenType::StrictNotLess,
+        _ => return None,
+    };
+    Some(token_type)
+}
+
 #[cfg(test)]
+#[allow(clippy::get_first)]
 mod tests {
     use super::*;
 
@@ -306,6 +598,39 @@ mod tests {
         assert_eq!(result[0].tokens[0].range.end.character, 2);
     }
     #[test]
+    fn lex_crlf_lines() {
+        let mut lexer = Lexer::new("say 'é'\r\n/* a\r\n b */ x\r\r\ny");
+        let tokens = lexer
+            .tokenize()
+            .into_iter()
+            .flat_map(|line| line.tokens)
+            .filter(|token| token.token_type != TokenType::Whitespace)
+            .map(|token| {
+                let start = &token.range.start;
+                (
+                    lexer.get_text(&token).to_string(),
+                    start.line,
+                    start.character,
+                )
+            })
+            .collect::<Vec<_>>();
+        let expected = [
+            ("say", 0, 0),
+            ("'é'", 0, 4),
+            ("\r\n", 0, 7),
+            ("/* a\r\n b */", 1, 0),
+            ("x", 2, 6),
+            ("\r", 2, 7),
+            ("\r\n", 3, 0),
+            ("y", 4, 0),
+            ("", 4, 1),
+        ];
+        let expected = expected
+            .map(|(text, line, character)| (text.to_string(), line, character))
+            .to_vec();
+        assert_eq!(tokens, expected);
+    }
+    #[test]
     fn lex_line5() {
         let mut lexer = Lexer::new("/*\n *\n */\nx:");
         let result = lexer.tokenize();
@@ -313,5 +638,266 @@ mod tests {
         assert_eq!(result[0].tokens[0].range.end.character, 3);
     }
 
+    fn token_types(source: &str) -> Vec<TokenType> {
+        let mut lexer = Lexer::new(source);
+        lexer
+            .tokenize()
+            .into_iter()
+            .flat_map(|line| line.tokens)
+            .map(|token| token.token_type)
+            .filter(|t| *t != TokenType::Whitespace && *t != TokenType::EOS)
+            .collect()
+    }
+
+    #[test]
+    fn lex_operators() {
+        assert_eq!(
+            token_types("+ - * ** / // % || & | && ( )"),
+            vec![
+                TokenType::Plus,
+                TokenType::Minus,
+                TokenType::Multiply,
+                TokenType::Power,
+                TokenType::Divide,
+                TokenType::Remainder,
+                TokenType::IntegerDivide,
+                TokenType::Concatenate,
+                TokenType::And,
+                TokenType::Or,
+                TokenType::Xor,
+                TokenType::LeftParen,
+                TokenType::RightParen,
+            ]
+        );
+    }
+    #[test]
+    fn lex_comparison_operators() {
+        assert_eq!(
+            token_types("=;==;\\=;\\==;<>;><;>;<;>=;<=;\\>;\\<;>>;<<;>>=;<<=;\\>>;\\<<")
+                .into_iter()
+                .filter(|t| *t != TokenType::Semicolon)
+                .collect::<Vec<_>>(),
+            vec![
+                TokenType::Equal,
+                TokenType::StrictEqual,
+                TokenType::NotEqual,
+                TokenType::StrictNotEqual,
+                TokenType::NotEqual,
+                TokenType::NotEqual,
+                TokenType::Greater,
+                TokenType::Less,
+                TokenType::GreaterOrEqual,
+                TokenType::LessOrEqual,
+                TokenType::NotGreater,
+                TokenType::NotLess,
+                TokenType::StrictGreater,
+                TokenType::StrictLess,
+                TokenType::StrictGreaterOrEqual,
+                TokenType::StrictLessOrEqual,
+                TokenType::StrictNotGreater,
+                TokenType::StrictNotLess,
+            ]
+        );
+    }
+    #[test]
+    fn lex_not_signs() {
+        assert_eq!(
+            token_types("\\;¬;^;¬=;^==")
+                .into_iter()
+                .filter(|t| *t != TokenType::Semicolon)
+                .collect::<Vec<_>>(),
+            vec![
+                TokenType::Not,
+                TokenType::Not,
+                TokenType::Not,
+                TokenType::NotEqual,
+                TokenType::StrictNotEqual,
+            ]
+        );
+    }
+    #[test]
+    fn lex_operator_with_blanks() {
+        let mut lexer = Lexer::new("a > = b");
+        let result = lexer.tokenize();
+        let operator = &result[0].tokens[2];
+        assert_eq!(operator.token_type, TokenType::GreaterOrEqual);
+        assert_eq!("> =", lexer.get_text(operator));
+        assert_eq!(
+            token_types("a = -1"),
+            vec![
+                TokenType::Symbol(SymbolKind::Simple),
+                TokenType::Equal,
+                TokenType::Minus,
+                TokenType::Symbol(SymbolKind::Number),
+            ]
+        );
+        // Blanks are only allowed inside comparison operators.
+        assert_eq!(
+            token_types("* *"),
+            vec![TokenType::Multiply, TokenType::Multiply]
+        );
+    }
+
+    #[test]
+    fn lex_strings() {
+        let mut lexer = Lexer::new("'it''s' \"say \"\"hi\"\"\" 'a\\'");
+        let result = lexer.tokenize();
+        let tokens = &result[0].tokens;
+        assert_eq!(tokens[0].token_type, TokenType::Literal);
+        assert_eq!("'it''s'", lexer.get_text(&tokens[0]));
+        assert_eq!("\"say \"\"hi\"\"\"", lexer.get_text(&tokens[2]));
+        assert_eq!("'a\\'", lexer.get_text(&tokens[4]));
+        assert!(lexer.diagnostics().is_empty());
+    }
+    #[test]
+    fn lex_hex_and_binary_strings() {
+        assert_eq!(
+            token_types("'0A 1b'x \"0101 11110000\"B 'abc'xyz 'ff'X"),
+            vec![
+                TokenType::HexString,
+                TokenType::BinaryString,
+                TokenType::Literal,
+                TokenType::Symbol(SymbolKind::Simple),
+                TokenType::HexString,
+            ]
+        );
+    }
+    #[test]
+    fn lex_invalid_hex_and_binary_strings() {
+        let diagnostics = |source| {
+            let mut lexer = Lexer::new(source);
+            lexer.tokenize();
+            lexer.diagnostics().to_vec()
+        };
+        assert!(diagnostics("'1 23 45'x '1 0101'b ''x").is_empty());
+        assert!(matches!(
+            diagnostics("'0G'x")[..],
+            [LexError::InvalidDigit {
+                binary: false,
+                found: 'G',
+                ..
+            }]
+        ));
+        assert!(matches!(
+            diagnostics("'012'b")[..],
+            [LexError::InvalidDigit {
+                binary: true,
+                found: '2',
+                ..
+            }]
+        ));
+        assert!(matches!(
+            diagnostics("'12 345'x")[..],
+            [LexError::InvalidBlank {
+                binary: false,
+                position: 3,
+                ..
+            }]
+        ));
+        assert!(matches!(
+            diagnostics("' 12'x")[..],
+            [LexError::InvalidBlank { position: 1, .. }]
+        ));
+        assert!(matches!(
+            diagnostics("'1 010'b")[..],
+            [LexError::InvalidBlank {
+                binary: true,
+                position: 2,
+                ..
+            }]
+        ));
+    }
+    #[test]
+    fn lex_unterminated_string() {
+        let mut lexer = Lexer::new("say 'abc\nx = 5");
+        let result = lexer.tokenize();
+        assert_eq!(result.len(), 2);
+        assert_eq!("'abc", lexer.get_text(&result[0].tokens[2]));
+        assert_eq!(result[1].tokens[0].range.start.line, 1);
+        assert!(matches!(
+            lexer.diagnostics(),
+            [LexError::UnterminatedString { quote: '\'', .. }]
+        ));
+    }
+
+    #[test]
+    fn lex_symbols() {
+        assert_eq!(
+            token_types("x !flag ?x @var #count $amt stem. 3E . a+1"),
+            vec![
+                TokenType::Symbol(SymbolKind::Simple),
+                TokenType::Symbol(SymbolKind::Simple),
+                TokenType::Symbol(SymbolKind::Simple),
+                TokenType::Symbol(SymbolKind::Simple),
+                TokenType::Symbol(SymbolKind::Simple),
+                TokenType::Symbol(SymbolKind::Simple),
+                TokenType::Symbol(SymbolKind::Stem),
+                TokenType::Symbol(SymbolKind::Constant),
+                TokenType::Symbol(SymbolKind::Constant),
+                TokenType::Symbol(SymbolKind::Simple),
+                TokenType::Plus,
+                TokenType::Symbol(SymbolKind::Number),
+            ]
+        );
+    }
+    #[test]
+    fn lex_compound_symbol() {
+        let mut lexer = Lexer::new("stem.i.j");
+        let result = lexer.tokenize();
+        let token = &result[0].tokens[0];
+        assert_eq!("stem.i.j", lexer.get_text(token));
+        let TokenType::Symbol(SymbolKind::Compound { tail }) = &token.token_type else {
+            panic!("not a compound symbol: {:?}", token.token_type);
+        };
+        let parts = tail
+            .iter()
+            .map(|range| &lexer.source[range.start.index..range.end.index])
+            .collect::<Vec<_>>();
+        assert_eq!(parts, vec!["i", "j"]);
+    }
+    #[test]
+    fn lex_numbers() {
+        let mut lexer = Lexer::new("1.5E+3 .5 12 1e-2 1E+ 3E-x");
+        let result = lexer.tokenize();
+        let symbols = result[0]
+            .tokens
+            .iter()
+            .filter(|t| matches!(t.token_type, TokenType::Symbol(_)))
+            .map(|t| (lexer.get_text(t), t.token_type.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            symbols,
+            vec![
+                ("1.5E+3", TokenType::Symbol(SymbolKind::Number)),
+                (".5", TokenType::Symbol(SymbolKind::Number)),
+                ("12", TokenType::Symbol(SymbolKind::Number)),
+                ("1e-2", TokenType::Symbol(SymbolKind::Number)),
+                ("1E", TokenType::Symbol(SymbolKind::Constant)),
+                ("3E", TokenType::Symbol(SymbolKind::Constant)),
+                ("x", TokenType::Symbol(SymbolKind::Simple)),
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_one_exponent() {
+        let mut lexer = Lexer::new("1E5E+3");
+        let result = lexer.tokenize();
+        let tokens = result[0]
+            .tokens
+            .iter()
+            .filter(|t| !matches!(t.token_type, TokenType::EOS))
+            .map(|t| (lexer.get_text(t), t.token_type.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                ("1E5E", TokenType::Symbol(SymbolKind::Constant)),
+                ("+", TokenType::Plus),
+                ("3", TokenType::Symbol(SymbolKind::Number)),
+            ]
+        );
+    }
+
     // TODO http://www.manmrk.net/tutorials/rexx/rexxvmref.pdf page 29
 }
@@ -1,5 +1,6 @@
-mod types;
+#[allow(clippy::module_inception)]
 pub mod lexer;
+mod types;
 
 pub use lexer::Lexer;
-pub use types::*;
\ No newline at end of file
+pub use types::*;
@@ -1,42 +1,122 @@
-use crate::ast::Span;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum TokenType {
     Whitespace,
     Comment,
     Literal,
-    Number,
+    HexString,
+    BinaryString,
+    Symbol(SymbolKind),
     Comma,
     Colon,
-    Identifier,
     Semicolon,
-    Equal,
-    Todo,
+    LeftParen,
+    RightParen,
+
+    // Arithmetic operators
+    Plus,          // +
+    Minus,         // -
+    Multiply,      // *
+    Power,         // **
+    Divide,        // /
+    IntegerDivide, // %
+    Remainder,     // //
+
+    // Concatenation and logical operators
+    Concatenate, // ||
+    Not,         // \ ¬ ^
+    And,         // &
+    Or,          // |
+    Xor,         // &&
+
+    // Normal comparison operators
+    Equal,          // =
+    NotEqual,       // \= <> ><
+    Greater,        // >
+    Less,           // <
+    GreaterOrEqual, // >=
+    LessOrEqual,    // <=
+    NotGreater,     // \>
+    NotLess,        // \<
+
+    // Strict comparison operators
+    StrictEqual,          // ==
+    StrictNotEqual,       // \==
+    StrictGreater,        // >>
+    StrictLess,           // <<
+    StrictGreaterOrEqual, // >>=
+    StrictLessOrEqual,    // <<=
+    StrictNotGreater,     // \>>
+    StrictNotLess,        // \<<
+
     Unknown,
     EOL, // the end of the line
     EOS, // the end of the source
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// The classification of a symbol token, as in the standard.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum SymbolKind {
+    /// A constant symbol that is a number, such as `12`, `.5` or `1.5E+3`.
+    Number,
+    /// Any other constant symbol: it starts with a digit or a period (`3E`, `.`).
+    Constant,
+    /// A symbol without periods, such as `x` or `!flag`.
+    Simple,
+    /// A symbol whose only period is its last character, such as `stem.`.
+    Stem,
+    /// A stem followed by tail parts, such as `stem.i.j`. Each tail part is
+    /// the range of the text between two periods and may be empty.
+    Compound { tail: Vec<Range> },
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub line: usize,
     pub character: usize,
     pub index: usize,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Range {
     pub start: Position,
     pub end: Position,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Token {
     pub token_type: TokenType,
     pub range: Range,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct LogicalLine {
     pub tokens: Vec<Token>,
 }
+
+/// Errors found while tokenizing; the tokens are still produced.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum LexError {
+    /// A comment is not closed before the end of the source. The range is
+    /// the opening `/*`.
+    UnterminatedComment { range: Range },
+    /// A string is not closed before the end of the line.
+    UnterminatedString { quote: char, range: Range },
+    /// A blank in a hexadecimal or binary string that is not between two
+    /// groups. The position is the one-based character position in the string.
+    InvalidBlank {
+        binary: bool,
+        position: usize,
+        range: Range,
+    },
+    /// A character that is not a hexadecimal or binary digit.
+    InvalidDigit {
+        binary: bool,
+        found: char,
+        range: Range,
+    },
+    /// A character that cannot start any token.
+    InvalidCharacter { found: char, range: Range },
+}
@@ -0,0 +1,123 @@
+//! A lexer and parser for the REXX language, as described by the ANSI
+//! standard (X3.274-1996).
+//!
+//! [`parse_str`] and [`parse_file`] parse a whole source in one call into a
+//! self-contained [`SourceFile`]. The
+//! [`Lexer`] and [`RexxParser`] give finer control, and [`syntax`] has a
+//! lossless syntax tree for tools that must keep comments and blanks.
+
+pub mod ast;
+pub mod checks;
+pub mod diagnostics;
+pub mod lexer;
+pub mod parser;
+pub mod syntax;
+
+use std::{io, path::Path, sync::Arc};
+
+pub use diagnostics::Diagnostic;
+pub use lexer::Lexer;
+pub use parser::RexxParser;
+
+use ast::Program;
+
+/// A parsed source, which shares its text with every clone of it. Parsing
+/// goes on after errors, so the program has every instruction that could
+/// be parsed. Read the text of its tokens and nodes with
+/// [`Spanned::text`](ast::Spanned::text).
+#[derive(Debug)]
+pub struct SourceFile {
+    text: Arc<str>,
+    pub program: Program,
+    /// The diagnostics of the lexer, the parser and the [`checks`], in
+    /// source order.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl SourceFile {
+    pub fn parse(text: impl Into<Arc<str>>) -> SourceFile {
+        let text = text.into();
+        let mut parser = RexxParser::new(&text);
+        let (program, mut diagnostics) = parser.parse_recovering();
+        diagnostics.extend(
+            checks::check(&program, &text)
+                .iter()
+                .map(|warning| Diagnostic::from_warning(warning, &text)),
+        );
+        diagnostics.sort_by_key(|diagnostic| diagnostic.range.start.index);
+        SourceFile {
+            text,
+            program,
+            diagnostics,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The text as a shared string.
+    pub fn shared_text(&self) -> Arc<str> {
+        self.text.clone()
+    }
+}
+
+pub fn parse_str(source: &str) -> SourceFile {
+    SourceFile::parse(source)
+}
+
+pub fn parse_file(path: impl AsRef<Path>) -> io::Result<SourceFile> {
+    let source = std::fs::read_to_string(path)?;
+    Ok(SourceFile::parse(source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{InstructionKind, Spanned};
+
+    #[test]
+    fn parse_in_one_call() {
+        let file = parse_str("say 'hello'\nx = (1\nexit");
+        assert!(matches!(
+            &file.program.instructions[..],
+            [first, last] if matches!(first.kind, InstructionKind::Say(_))
+                && matches!(last.kind, InstructionKind::Exit(None))
+        ));
+        let diagnostics = file
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec!["2:5: error [36] Unmatched \"(\" in expression"]
+        );
+        assert_eq!(
+            file.program.instructions[0].text(file.text()),
+            "say 'hello'"
+        );
+    }
+
+    #[test]
+    fn source_file_is_self_contained() {
+        let file = std::thread::spawn(|| parse_str("start:\n  call start"))
+            .join()
+            .unwrap();
+        let InstructionKind::Label(name) = &file.program.instructions[0].kind else {
+            panic!("unexpected instructions: {:?}", file.program.instructions);
+        };
+        assert_eq!(name.text(file.text()), "start");
+    }
+
+    #[test]
+    fn program_round_trips_through_json() {
+        let file = parse_str("do i = 1 to 3\n  parse arg a ',' b\nend\nx = (1");
+        let json = serde_json::to_string(&file.program).unwrap();
+        let program: ast::Program = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{program:?}"), format!("{:?}", file.program));
+        let json = serde_json::to_string(&file.diagnostics).unwrap();
+        let diagnostics: Vec<Diagnostic> = serde_json::from_str(&json).unwrap();
+        assert_eq!(diagnostics, file.diagnostics);
+    }
+}
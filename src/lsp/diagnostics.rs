@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use lsp_types::{DiagnosticSeverity, NumberOrString, PublishDiagnosticsParams, Uri};
+use rexx_parser::diagnostics::Severity;
+
+use super::documents::Document;
+
+/// How long a document must stay unchanged before its diagnostics are
+/// published, so that they do not flicker while typing.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The documents whose diagnostics are waiting to be published, with the
+/// time when they are due.
+#[derive(Default)]
+pub struct Pending(HashMap<Uri, Instant>);
+
+impl Pending {
+    /// Publishes the diagnostics of the document once it has not changed
+    /// for a while. A change before then pushes the time back.
+    pub fn schedule(&mut self, uri: Uri) {
+        self.0.insert(uri, Instant::now() + DEBOUNCE);
+    }
+
+    pub fn cancel(&mut self, uri: &Uri) {
+        self.0.remove(uri);
+    }
+
+    /// When the next document is due, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.0.values().min().copied()
+    }
+
+    /// Removes and returns the documents that are due.
+    pub fn take_due(&mut self) -> Vec<Uri> {
+        let now = Instant::now();
+        let due = self
+            .0
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(uri, _)| uri.clone())
+            .collect::<Vec<_>>();
+        due.iter().for_each(|uri| self.cancel(uri));
+        due
+    }
+}
+
+/// The diagnostics of the lexer, the parser and the checks for a document,
+/// with their REXX error codes.
+pub fn publish_params(uri: Uri, document: &Document) -> PublishDiagnosticsParams {
+    let diagnostics = document
+        .file()
+        .diagnostics
+        .iter()
+        .map(|diagnostic| lsp_types::Diagnostic {
+            range: document.lines.range(&diagnostic.range),
+            severity: Some(match diagnostic.severity {
+                Severity::Error => DiagnosticSeverity::ERROR,
+                Severity::Warning => DiagnosticSeverity::WARNING,
+            }),
+            code: diagnostic
+                .code
+                .map(|code| NumberOrString::String(code.to_string())),
+            source: Some("rexx".to_string()),
+            message: diagnostic.message.clone(),
+            ..Default::default()
+        })
+        .collect();
+    PublishDiagnosticsParams::new(uri, diagnostics, Some(document.version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{Position, Range};
+
+    #[test]
+    fn convert_diagnostics() {
+        let uri: Uri = "file:///tmp/diagnostics.rexx".parse().unwrap();
+        let document = Document::new(3, "say 'ü'; ~\n/* open".to_string());
+        let params = publish_params(uri, &document);
+        assert_eq!(params.version, Some(3));
+        let found = params
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let Some(NumberOrString::String(code)) = &diagnostic.code else {
+                    panic!("unexpected code: {:?}", diagnostic.code);
+                };
+                (diagnostic.range, code.as_str(), diagnostic.severity)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                (
+                    Range::new(Position::new(0, 9), Position::new(0, 10)),
+                    "13.1",
+                    Some(DiagnosticSeverity::ERROR)
+                ),
+                (
+                    Range::new(Position::new(1, 0), Position::new(1, 2)),
+                    "6.1",
+                    Some(DiagnosticSeverity::ERROR)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn debounce_changes() {
+        let uri: Uri = "file:///tmp/debounce.rexx".parse().unwrap();
+        let mut pending = Pending::default();
+        assert_eq!(pending.deadline(), None);
+        pending.schedule(uri.clone());
+        let first = pending.deadline().unwrap();
+        assert!(pending.take_due().is_empty());
+        pending.schedule(uri.clone());
+        assert!(pending.deadline().unwrap() >= first);
+        std::thread::sleep(DEBOUNCE);
+        assert_eq!(pending.take_due(), [uri]);
+        assert_eq!(pending.deadline(), None);
+    }
+}
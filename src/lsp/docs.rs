@@ -0,0 +1,593 @@
+/// The documentation of a keyword instruction or a built-in function.
+pub struct Entry {
+    pub name: &'static str,
+    /// The forms of the instruction or function, one per line, and the
+    /// parts they refer to.
+    pub syntax: &'static str,
+    pub description: &'static str,
+}
+
+impl Entry {
+    /// The syntax as a REXX code block followed by the description.
+    pub fn markdown(&self) -> String {
+        format!("```rexx\n{}\n```\n\n{}", self.syntax, self.description)
+    }
+}
+
+/// The keyword instruction with the given uppercase name.
+pub fn keyword(name: &str) -> Option<&'static Entry> {
+    KEYWORDS.iter().find(|entry| entry.name == name)
+}
+
+/// The built-in function with the given uppercase name.
+pub fn function(name: &str) -> Option<&'static Entry> {
+    FUNCTIONS.iter().find(|entry| entry.name == name)
+}
+
+const KEYWORDS: &[Entry] = &[
+    Entry {
+        name: "ADDRESS",
+        syntax: "ADDRESS environment [command]\nADDRESS [VALUE] expression\nADDRESS",
+        description: "Sends a single command to an environment, or changes the environment \
+                      that commands are sent to. Without operands, the previous environment \
+                      is restored.",
+    },
+    Entry {
+        name: "ARG",
+        syntax: "ARG [template_list]",
+        description: "Parses the arguments of the program or routine into variables, after \
+                      translating them to uppercase. Short for `PARSE UPPER ARG`.",
+    },
+    Entry {
+        name: "CALL",
+        syntax: "CALL name [expression] [, [expression]]...\n\
+                 CALL ON condition [NAME trapname]\nCALL OFF condition",
+        description: "Invokes an internal routine, a built-in function or an external \
+                      routine. The result, if any, is assigned to `RESULT`. The ON and OFF \
+                      forms enable or disable a condition trap.",
+    },
+    Entry {
+        name: "DO",
+        syntax: "DO [repetitor] [conditional]\n  [instruction]...\nEND [name]\n\n\
+                 repetitor:   name = expri [TO exprt] [BY exprb] [FOR exprf]\n\
+                 \x20            | exprr | FOREVER\n\
+                 conditional: WHILE exprw | UNTIL expru",
+        description: "Groups instructions together and optionally runs them repeatedly. A \
+                      WHILE condition is tested before each pass and an UNTIL condition \
+                      after it.",
+    },
+    Entry {
+        name: "DROP",
+        syntax: "DROP name...\nDROP (name)...",
+        description: "Unassigns variables, which restores them to their original \
+                      uninitialized state. A name in parentheses is a variable whose value \
+                      lists the names to drop.",
+    },
+    Entry {
+        name: "EXIT",
+        syntax: "EXIT [expression]",
+        description: "Ends the program unconditionally, returning the value of the \
+                      expression, if any, to the caller.",
+    },
+    Entry {
+        name: "IF",
+        syntax: "IF expression THEN instruction\n[ELSE instruction]",
+        description: "Runs the instruction after THEN when the expression is `1`, or the one \
+                      after ELSE, if any, when it is `0`. Any other value is an error.",
+    },
+    Entry {
+        name: "INTERPRET",
+        syntax: "INTERPRET expression",
+        description: "Runs the value of the expression as REXX instructions, as if they \
+                      were in the program at this point.",
+    },
+    Entry {
+        name: "ITERATE",
+        syntax: "ITERATE [name]",
+        description: "Ends the current pass of the innermost repetitive DO loop, or of the \
+                      loop whose control variable is `name`, and starts the next one.",
+    },
+    Entry {
+        name: "LEAVE",
+        syntax: "LEAVE [name]",
+        description: "Ends the innermost repetitive DO loop, or the loop whose control \
+                      variable is `name`, and continues after its END.",
+    },
+    Entry {
+        name: "NOP",
+        syntax: "NOP",
+        description: "Does nothing. Useful as the target of a THEN or an ELSE.",
+    },
+    Entry {
+        name: "NUMERIC",
+        syntax: "NUMERIC DIGITS [expression]\n\
+                 NUMERIC FORM [SCIENTIFIC | ENGINEERING | [VALUE] expression]\n\
+                 NUMERIC FUZZ [expression]",
+        description: "Sets the precision of arithmetic, the form of exponential notation, \
+                      or the number of digits ignored by numeric comparisons.",
+    },
+    Entry {
+        name: "OPTIONS",
+        syntax: "OPTIONS expression",
+        description: "Passes options to the language processor. Options it does not \
+                      recognize are ignored.",
+    },
+    Entry {
+        name: "PARSE",
+        syntax: "PARSE [UPPER | LOWER] [CASELESS] source [template_list]\n\n\
+                 source: ARG | LINEIN | PULL | SOURCE | VERSION\n\
+                 \x20       | VALUE [expression] WITH | VAR name",
+        description: "Assigns parts of a string to variables, as described by the \
+                      templates. The string comes from the arguments, the input stream, the \
+                      queue, the program source, the language version, an expression or a \
+                      variable.",
+    },
+    Entry {
+        name: "PROCEDURE",
+        syntax: "PROCEDURE [EXPOSE name...]",
+        description: "Hides the variables of the caller from an internal routine, except \
+                      those exposed. It must be the first instruction after the label of \
+                      the routine.",
+    },
+    Entry {
+        name: "PULL",
+        syntax: "PULL [template_list]",
+        description: "Reads a line from the queue, or the input stream when the queue is \
+                      empty, and parses it in uppercase. Short for `PARSE UPPER PULL`.",
+    },
+    Entry {
+        name: "PUSH",
+        syntax: "PUSH [expression]",
+        description: "Adds the value of the expression at the head of the queue (last in, \
+                      first out).",
+    },
+    Entry {
+        name: "QUEUE",
+        syntax: "QUEUE [expression]",
+        description: "Adds the value of the expression at the tail of the queue (first in, \
+                      first out).",
+    },
+    Entry {
+        name: "RETURN",
+        syntax: "RETURN [expression]",
+        description: "Returns from a routine or a function to its caller, with the value of \
+                      the expression. A function must return a value.",
+    },
+    Entry {
+        name: "SAY",
+        syntax: "SAY [expression]",
+        description: "Writes the value of the expression, followed by a new line, to the \
+                      default output stream.",
+    },
+    Entry {
+        name: "SELECT",
+        syntax: "SELECT\n  WHEN expression THEN instruction\n  ...\n\
+                 [OTHERWISE [instruction]...]\nEND",
+        description: "Runs the instruction of the first WHEN whose expression is `1`, or \
+                      the instructions after OTHERWISE when none is. Without OTHERWISE, \
+                      that is an error.",
+    },
+    Entry {
+        name: "SIGNAL",
+        syntax: "SIGNAL label\nSIGNAL [VALUE] expression\n\
+                 SIGNAL ON condition [NAME trapname]\nSIGNAL OFF condition",
+        description: "Transfers control to a label, ending all active DO, IF and SELECT \
+                      instructions. The ON and OFF forms enable or disable a condition \
+                      trap.",
+    },
+    Entry {
+        name: "TRACE",
+        syntax: "TRACE [[?] A | C | E | F | I | L | N | O | R]\nTRACE [VALUE] expression",
+        description: "Sets what is traced while the program runs: All, Commands, Errors, \
+                      Failure, Intermediates, Labels, Normal, Off or Results. `?` switches \
+                      interactive tracing.",
+    },
+];
+
+const FUNCTIONS: &[Entry] = &[
+    Entry {
+        name: "ABBREV",
+        syntax: "ABBREV(information, info [, length])",
+        description: "Returns `1` if `info` is equal to the leading characters of \
+                      `information` and is at least `length` characters long, or `0`.",
+    },
+    Entry {
+        name: "ABS",
+        syntax: "ABS(number)",
+        description: "Returns the absolute value of `number`.",
+    },
+    Entry {
+        name: "ADDRESS",
+        syntax: "ADDRESS()",
+        description: "Returns the name of the environment that commands are sent to.",
+    },
+    Entry {
+        name: "ARG",
+        syntax: "ARG([n [, option]])",
+        description: "Returns the number of arguments, the `n`th argument, or with option \
+                      `E` or `O` whether the `n`th argument exists or is omitted.",
+    },
+    Entry {
+        name: "B2X",
+        syntax: "B2X(binary_string)",
+        description: "Converts a string of binary digits to hexadecimal.",
+    },
+    Entry {
+        name: "BITAND",
+        syntax: "BITAND(string1 [, [string2] [, pad]])",
+        description: "Returns the bitwise AND of the two strings, padding the shorter one \
+                      with `pad`.",
+    },
+    Entry {
+        name: "BITOR",
+        syntax: "BITOR(string1 [, [string2] [, pad]])",
+        description: "Returns the bitwise inclusive OR of the two strings, padding the \
+                      shorter one with `pad`.",
+    },
+    Entry {
+        name: "BITXOR",
+        syntax: "BITXOR(string1 [, [string2] [, pad]])",
+        description: "Returns the bitwise exclusive OR of the two strings, padding the \
+                      shorter one with `pad`.",
+    },
+    Entry {
+        name: "C2D",
+        syntax: "C2D(string [, length])",
+        description: "Converts the binary representation of a string to a decimal number. \
+                      With `length`, the string is taken as a signed number of that many \
+                      characters.",
+    },
+    Entry {
+        name: "C2X",
+        syntax: "C2X(string)",
+        description: "Converts a string to its hexadecimal representation.",
+    },
+    Entry {
+        name: "CENTER",
+        syntax: "CENTER(string, length [, pad])",
+        description: "Returns `string` centered in a string of `length` characters, padded \
+                      with `pad` or truncated on both sides. Also spelled `CENTRE`.",
+    },
+    Entry {
+        name: "CENTRE",
+        syntax: "CENTRE(string, length [, pad])",
+        description: "Returns `string` centered in a string of `length` characters, padded \
+                      with `pad` or truncated on both sides. Also spelled `CENTER`.",
+    },
+    Entry {
+        name: "CHANGESTR",
+        syntax: "CHANGESTR(needle, haystack, newneedle)",
+        description: "Returns `haystack` with every occurrence of `needle` replaced by \
+                      `newneedle`.",
+    },
+    Entry {
+        name: "CHARIN",
+        syntax: "CHARIN([stream] [, [start] [, length]])",
+        description: "Reads `length` characters, one by default, from a character input \
+                      stream, starting at `start` if given.",
+    },
+    Entry {
+        name: "CHAROUT",
+        syntax: "CHAROUT([stream] [, [string] [, start]])",
+        description: "Writes `string` to a character output stream, starting at `start` if \
+                      given. Returns the number of characters that could not be written.",
+    },
+    Entry {
+        name: "CHARS",
+        syntax: "CHARS([stream])",
+        description: "Returns the number of characters remaining in a character input \
+                      stream, or `1` when it cannot be determined.",
+    },
+    Entry {
+        name: "COMPARE",
+        syntax: "COMPARE(string1, string2 [, pad])",
+        description: "Returns `0` if the strings are equal after padding the shorter one \
+                      with `pad`, or the position of the first character that differs.",
+    },
+    Entry {
+        name: "CONDITION",
+        syntax: "CONDITION([option])",
+        description: "Returns information about the current trapped condition: its \
+                      Condition name, Description, Instruction (CALL or SIGNAL) or Status.",
+    },
+    Entry {
+        name: "COPIES",
+        syntax: "COPIES(string, n)",
+        description: "Returns `n` concatenated copies of `string`.",
+    },
+    Entry {
+        name: "COUNTSTR",
+        syntax: "COUNTSTR(needle, haystack)",
+        description: "Returns the number of occurrences of `needle` in `haystack` that do \
+                      not overlap.",
+    },
+    Entry {
+        name: "D2C",
+        syntax: "D2C(wholenumber [, length])",
+        description: "Converts a whole number to the string of its binary representation, \
+                      of `length` characters if given.",
+    },
+    Entry {
+        name: "D2X",
+        syntax: "D2X(wholenumber [, length])",
+        description: "Converts a whole number to hexadecimal, of `length` digits if given.",
+    },
+    Entry {
+        name: "DATATYPE",
+        syntax: "DATATYPE(string [, type])",
+        description: "Returns `NUM` or `CHAR`, or with `type` whether the string is of that \
+                      type: Alphanumeric, Binary, Lowercase, Mixed case, Number, Symbol, \
+                      Uppercase, Whole number, Variable or heXadecimal.",
+    },
+    Entry {
+        name: "DATE",
+        syntax: "DATE([option_out [, date [, option_in]]])",
+        description: "Returns the local date in the format of `option_out`, or converts \
+                      `date` from the format of `option_in`.",
+    },
+    Entry {
+        name: "DELSTR",
+        syntax: "DELSTR(string, n [, length])",
+        description: "Deletes `length` characters from `string`, or the rest of it, \
+                      starting at the `n`th character.",
+    },
+    Entry {
+        name: "DELWORD",
+        syntax: "DELWORD(string, n [, length])",
+        description: "Deletes `length` blank-delimited words from `string`, or the rest of \
+                      them, starting at the `n`th word.",
+    },
+    Entry {
+        name: "DIGITS",
+        syntax: "DIGITS()",
+        description: "Returns the current setting of NUMERIC DIGITS.",
+    },
+    Entry {
+        name: "ERRORTEXT",
+        syntax: "ERRORTEXT(n [, option])",
+        description: "Returns the message of the REXX error number `n`.",
+    },
+    Entry {
+        name: "FORM",
+        syntax: "FORM()",
+        description: "Returns the current setting of NUMERIC FORM.",
+    },
+    Entry {
+        name: "FORMAT",
+        syntax: "FORMAT(number [, [before] [, [after] [, [expp] [, expt]]]])",
+        description: "Rounds and formats `number` with `before` integer and `after` \
+                      decimal characters, and `expp` exponent digits when the exponent is \
+                      needed beyond `expt` digits.",
+    },
+    Entry {
+        name: "FUZZ",
+        syntax: "FUZZ()",
+        description: "Returns the current setting of NUMERIC FUZZ.",
+    },
+    Entry {
+        name: "INSERT",
+        syntax: "INSERT(new, target [, [n] [, [length] [, pad]]])",
+        description: "Inserts `new`, padded or truncated to `length`, into `target` after \
+                      its `n`th character.",
+    },
+    Entry {
+        name: "LASTPOS",
+        syntax: "LASTPOS(needle, haystack [, start])",
+        description: "Returns the position of the last occurrence of `needle` in \
+                      `haystack`, searching backwards from `start`, or `0`.",
+    },
+    Entry {
+        name: "LEFT",
+        syntax: "LEFT(string, length [, pad])",
+        description: "Returns the leftmost `length` characters of `string`, padded with \
+                      `pad` on the right if needed.",
+    },
+    Entry {
+        name: "LENGTH",
+        syntax: "LENGTH(string)",
+        description: "Returns the length of `string`.",
+    },
+    Entry {
+        name: "LINEIN",
+        syntax: "LINEIN([stream] [, [line] [, count]])",
+        description: "Reads a line from a character input stream, starting at line `line` \
+                      if given. A `count` of `0` only positions the stream.",
+    },
+    Entry {
+        name: "LINEOUT",
+        syntax: "LINEOUT([stream] [, [string] [, line]])",
+        description: "Writes `string` and a line end to a character output stream. \
+                      Returns `0` if it was written, or `1`.",
+    },
+    Entry {
+        name: "LINES",
+        syntax: "LINES([stream [, option]])",
+        description: "Returns the number of complete lines remaining in a character input \
+                      stream, or with option `N` whether any remain.",
+    },
+    Entry {
+        name: "MAX",
+        syntax: "MAX(number [, number]...)",
+        description: "Returns the largest of the numbers.",
+    },
+    Entry {
+        name: "MIN",
+        syntax: "MIN(number [, number]...)",
+        description: "Returns the smallest of the numbers.",
+    },
+    Entry {
+        name: "OVERLAY",
+        syntax: "OVERLAY(new, target [, [n] [, [length] [, pad]]])",
+        description: "Overwrites `target`, from its `n`th character, with `new` padded or \
+                      truncated to `length`.",
+    },
+    Entry {
+        name: "POS",
+        syntax: "POS(needle, haystack [, start])",
+        description: "Returns the position of the first occurrence of `needle` in \
+                      `haystack`, searching from `start`, or `0`.",
+    },
+    Entry {
+        name: "QUALIFY",
+        syntax: "QUALIFY([stream])",
+        description: "Returns a name for the stream that stays valid for as long as the \
+                      stream exists.",
+    },
+    Entry {
+        name: "QUEUED",
+        syntax: "QUEUED()",
+        description: "Returns the number of lines in the queue.",
+    },
+    Entry {
+        name: "RANDOM",
+        syntax: "RANDOM([max])\nRANDOM([min] [, [max] [, seed]])",
+        description: "Returns a pseudo-random whole number between `min` and `max`, `0` \
+                      and `999` by default. A `seed` makes the sequence repeatable.",
+    },
+    Entry {
+        name: "REVERSE",
+        syntax: "REVERSE(string)",
+        description: "Returns `string` with its characters in reverse order.",
+    },
+    Entry {
+        name: "RIGHT",
+        syntax: "RIGHT(string, length [, pad])",
+        description: "Returns the rightmost `length` characters of `string`, padded with \
+                      `pad` on the left if needed.",
+    },
+    Entry {
+        name: "SIGN",
+        syntax: "SIGN(number)",
+        description: "Returns `-1`, `0` or `1` as `number` is negative, zero or positive.",
+    },
+    Entry {
+        name: "SOURCELINE",
+        syntax: "SOURCELINE([n])",
+        description: "Returns the number of lines in the program source, or its `n`th \
+                      line.",
+    },
+    Entry {
+        name: "SPACE",
+        syntax: "SPACE(string [, [n] [, pad]])",
+        description: "Returns the blank-delimited words of `string` with `n` `pad` \
+                      characters, one blank by default, between each pair of words.",
+    },
+    Entry {
+        name: "STREAM",
+        syntax: "STREAM(stream [, [option] [, command]])",
+        description: "Returns the state of a stream with options `D` or `S`, or runs a \
+                      stream command with option `C`.",
+    },
+    Entry {
+        name: "STRIP",
+        syntax: "STRIP(string [, [option] [, char]])",
+        description: "Removes leading (`L`), trailing (`T`) or both (`B`, the default) \
+                      occurrences of `char`, a blank by default, from `string`.",
+    },
+    Entry {
+        name: "SUBSTR",
+        syntax: "SUBSTR(string, n [, [length] [, pad]])",
+        description: "Returns the substring of `string` that starts at the `n`th character \
+                      and is `length` characters long, padded with `pad` if needed.",
+    },
+    Entry {
+        name: "SUBWORD",
+        syntax: "SUBWORD(string, n [, length])",
+        description: "Returns `length` blank-delimited words of `string`, or the rest of \
+                      them, starting at the `n`th word.",
+    },
+    Entry {
+        name: "SYMBOL",
+        syntax: "SYMBOL(name)",
+        description: "Returns `VAR` if `name` is an assigned variable, `LIT` if it is a \
+                      valid symbol that is not, or `BAD` if it is not a valid symbol.",
+    },
+    Entry {
+        name: "TIME",
+        syntax: "TIME([option_out [, time [, option_in]]])",
+        description: "Returns the local time in the format of `option_out`, measures \
+                      elapsed time with `E` and `R`, or converts `time` from the format of \
+                      `option_in`.",
+    },
+    Entry {
+        name: "TRACE",
+        syntax: "TRACE([setting])",
+        description: "Returns the current trace setting, and changes it to `setting` if \
+                      given.",
+    },
+    Entry {
+        name: "TRANSLATE",
+        syntax: "TRANSLATE(string [, [tableo] [, [tablei] [, pad]]])",
+        description: "Replaces each character of `string` found in `tablei` with the \
+                      character at the same position in `tableo`. Without tables, \
+                      translates `string` to uppercase.",
+    },
+    Entry {
+        name: "TRUNC",
+        syntax: "TRUNC(number [, n])",
+        description: "Returns the integer part of `number` followed by `n` decimal places, \
+                      without rounding.",
+    },
+    Entry {
+        name: "VALUE",
+        syntax: "VALUE(name [, [newvalue] [, selector]])",
+        description: "Returns the value of the variable `name`, and assigns `newvalue` to \
+                      it if given. A `selector` names an external pool of variables.",
+    },
+    Entry {
+        name: "VERIFY",
+        syntax: "VERIFY(string, reference [, [option] [, start]])",
+        description: "Returns the position of the first character of `string` that is not \
+                      in `reference` (option `N`) or that is (option `M`), or `0`.",
+    },
+    Entry {
+        name: "WORD",
+        syntax: "WORD(string, n)",
+        description: "Returns the `n`th blank-delimited word of `string`.",
+    },
+    Entry {
+        name: "WORDINDEX",
+        syntax: "WORDINDEX(string, n)",
+        description: "Returns the position of the first character of the `n`th \
+                      blank-delimited word of `string`, or `0`.",
+    },
+    Entry {
+        name: "WORDLENGTH",
+        syntax: "WORDLENGTH(string, n)",
+        description: "Returns the length of the `n`th blank-delimited word of `string`.",
+    },
+    Entry {
+        name: "WORDPOS",
+        syntax: "WORDPOS(phrase, string [, start])",
+        description: "Returns the number of the word of `string`, searching from word \
+                      `start`, where the words of `phrase` first occur, or `0`.",
+    },
+    Entry {
+        name: "WORDS",
+        syntax: "WORDS(string)",
+        description: "Returns the number of blank-delimited words in `string`.",
+    },
+    Entry {
+        name: "X2B",
+        syntax: "X2B(hexstring)",
+        description: "Converts a string of hexadecimal digits to binary digits.",
+    },
+    Entry {
+        name: "X2C",
+        syntax: "X2C(hexstring)",
+        description: "Converts a string of hexadecimal digits to the characters they \
+                      represent.",
+    },
+    Entry {
+        name: "X2D",
+        syntax: "X2D(hexstring [, length])",
+        description: "Converts a string of hexadecimal digits to a decimal number. With \
+                      `length`, the string is taken as a signed number of that many digits.",
+    },
+    Entry {
+        name: "XRANGE",
+        syntax: "XRANGE([start] [, end])",
+        description: "Returns all the characters from `start` to `end`, `'00'x` and \
+                      `'FF'x` by default.",
+    },
+];
@@ -0,0 +1,193 @@
+use std::cell::OnceCell;
+use std::collections::HashMap;
+
+use lsp_types::{
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, Uri,
+};
+use rexx_parser::checks::Labels;
+use rexx_parser::lexer::Token;
+use rexx_parser::{Lexer, SourceFile};
+
+use super::line_index::LineIndex;
+
+/// The documents open in the editor, with their unsaved text. Requests are
+/// answered from here rather than from the files on disk.
+#[derive(Default)]
+pub struct Documents {
+    documents: HashMap<Uri, Document>,
+}
+
+pub struct Document {
+    /// The version the editor gave with the last change.
+    pub version: i32,
+    pub lines: LineIndex,
+    /// Parsed on first use, as a burst of changes comes in while typing.
+    file: OnceCell<SourceFile>,
+    tokens: OnceCell<Vec<Token>>,
+    labels: OnceCell<Labels>,
+}
+
+impl Document {
+    pub fn new(version: i32, text: String) -> Document {
+        Document {
+            version,
+            lines: LineIndex::new(text.into()),
+            file: OnceCell::new(),
+            tokens: OnceCell::new(),
+            labels: OnceCell::new(),
+        }
+    }
+
+    /// The parsed text. The first call after a change parses it.
+    pub fn file(&self) -> &SourceFile {
+        self.file
+            .get_or_init(|| SourceFile::parse(self.lines.shared_text()))
+    }
+
+    /// The tokens of the text, comments included, in source order.
+    pub fn tokens(&self) -> &[Token] {
+        self.tokens.get_or_init(|| {
+            let lines = Lexer::new(self.file().text()).tokenize();
+            lines.into_iter().flat_map(|line| line.tokens).collect()
+        })
+    }
+
+    /// The labels of the program and the references to them.
+    pub fn labels(&self) -> &Labels {
+        self.labels.get_or_init(|| Labels::of(&self.file().program))
+    }
+}
+
+impl Documents {
+    pub fn open(&mut self, params: DidOpenTextDocumentParams) {
+        let document = params.text_document;
+        self.documents
+            .insert(document.uri, Document::new(document.version, document.text));
+    }
+
+    /// Applies the changes in order. A change with a range replaces that
+    /// part of the text, and one without replaces the whole text. A range
+    /// whose end comes before its start is taken as empty. The line index
+    /// is updated with each change, and the document is parsed when it is
+    /// next needed.
+    pub fn change(&mut self, params: DidChangeTextDocumentParams) {
+        let Some(document) = self.documents.get_mut(&params.text_document.uri) else {
+            eprintln!(
+                "change to a document that is not open: {:?}",
+                params.text_document.uri
+            );
+            return;
+        };
+        let lines = &mut document.lines;
+        for change in params.content_changes {
+            match change.range {
+                Some(range) => {
+                    // Each range is relative to the text left by the
+                    // previous change.
+                    let start = lines.offset(range.start);
+                    let end = lines.offset(range.end).max(start);
+                    lines.replace(start..end, &change.text);
+                }
+                None => *lines = LineIndex::new(change.text.into()),
+            }
+        }
+        document.version = params.text_document.version;
+        document.file = OnceCell::new();
+        document.tokens = OnceCell::new();
+        document.labels = OnceCell::new();
+    }
+
+    pub fn close(&mut self, params: DidCloseTextDocumentParams) {
+        self.documents.remove(&params.text_document.uri);
+    }
+
+    pub fn get(&self, uri: &Uri) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{
+        Position, Range, TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+        VersionedTextDocumentIdentifier,
+    };
+
+    fn open(documents: &mut Documents, uri: &Uri, text: &str) {
+        documents.open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "rexx".to_string(),
+                version: 1,
+                text: text.to_string(),
+            },
+        });
+    }
+
+    #[test]
+    fn track_open_documents() {
+        let uri: Uri = "file:///tmp/unsaved.rexx".parse().unwrap();
+        let mut documents = Documents::default();
+        open(&mut documents, &uri, "first:\n");
+        assert_eq!(documents.get(&uri).unwrap().file().text(), "first:\n");
+
+        documents.change(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: 2,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "second:\n  return\n".to_string(),
+            }],
+        });
+        let document = documents.get(&uri).unwrap();
+        assert_eq!(document.version, 2);
+        assert!(document.file.get().is_none());
+        assert_eq!(document.file().text(), "second:\n  return\n");
+        assert_eq!(document.file().program.instructions.len(), 2);
+
+        documents.close(DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+        });
+        assert!(documents.get(&uri).is_none());
+    }
+
+    #[test]
+    fn apply_incremental_changes() {
+        let uri: Uri = "file:///tmp/incremental.rexx".parse().unwrap();
+        let mut documents = Documents::default();
+        open(&mut documents, &uri, "say 'é' x\nexit\n");
+        let edit =
+            |start: (u32, u32), end: (u32, u32), text: &str| TextDocumentContentChangeEvent {
+                range: Some(Range::new(
+                    Position::new(start.0, start.1),
+                    Position::new(end.0, end.1),
+                )),
+                range_length: None,
+                text: text.to_string(),
+            };
+        documents.change(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: 2,
+            },
+            content_changes: vec![
+                // The columns after the emoji move by two UTF-16 units.
+                edit((0, 5), (0, 6), "😀"),
+                edit((0, 9), (0, 10), "y"),
+                edit((1, 4), (1, 4), " 1"),
+                // A range that ends before it starts inserts at its start.
+                edit((1, 0), (0, 2), "# "),
+            ],
+        });
+        let document = documents.get(&uri).unwrap();
+        assert_eq!(document.file().text(), "say '😀' y\n# exit 1\n");
+        assert_eq!(
+            document.lines.position(document.file().text().len()),
+            Position::new(2, 0)
+        );
+    }
+}
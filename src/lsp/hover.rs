@@ -0,0 +1,433 @@
+use std::slice;
+
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+use rexx_parser::ast::visit::{walk_instruction, Visitor};
+use rexx_parser::ast::{
+    Instruction, InstructionKind, ParseSource, ParseSourceKind, RoutineName, SignalTarget,
+    SignalTargetKind, Spanned,
+};
+use rexx_parser::checks::label_name;
+use rexx_parser::lexer::{self, Token, TokenType};
+
+use super::docs;
+use super::documents::Document;
+
+/// What the cursor is on.
+enum Target {
+    /// The keyword that starts an instruction, or a keyword of its clauses
+    /// such as THEN or END, with the instruction that documents it.
+    Keyword {
+        range: lexer::Range,
+        instruction: String,
+    },
+    /// The name of a CALL or a function call. A string never names a label.
+    Routine { name: Token, literal: bool },
+    /// A label, or the target of a SIGNAL.
+    Label(Token),
+}
+
+impl Target {
+    fn range(&self) -> &lexer::Range {
+        match self {
+            Target::Keyword { range, .. } => range,
+            Target::Routine { name: token, .. } | Target::Label(token) => &token.range,
+        }
+    }
+}
+
+/// Finds the target under the cursor. The cursor may be just after it.
+struct Find<'a> {
+    source: &'a str,
+    tokens: &'a [Token],
+    offset: usize,
+    found: Option<Target>,
+}
+
+impl<'a> Find<'a> {
+    fn contains(&self, range: &lexer::Range) -> bool {
+        range.start.index <= self.offset && self.offset <= range.end.index
+    }
+
+    /// The symbols between two offsets that are the given keyword.
+    fn keywords(
+        &self,
+        keyword: &'static str,
+        start: usize,
+        end: usize,
+    ) -> impl Iterator<Item = &'a Token> {
+        let (tokens, source) = (self.tokens, self.source);
+        let first = tokens.partition_point(|token| token.range.start.index < start);
+        tokens[first..]
+            .iter()
+            .take_while(move |token| token.range.end.index <= end)
+            .filter(move |token| {
+                matches!(token.token_type, TokenType::Symbol(_))
+                    && token.text(source).eq_ignore_ascii_case(keyword)
+            })
+    }
+
+    /// Finds the keywords of the clauses of an IF, a SELECT or a DO, which
+    /// the tree does not keep, between the parts it does keep.
+    fn visit_clause_keywords(&mut self, instruction: &Instruction) {
+        let span = &instruction.span;
+        let (name, keywords) = match &instruction.kind {
+            InstructionKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let then = self
+                    .keywords(
+                        "THEN",
+                        condition.span().end.index,
+                        then_branch.span.start.index,
+                    )
+                    .next();
+                let else_keyword = else_branch.as_ref().and_then(|branch| {
+                    self.keywords("ELSE", then_branch.span.end.index, branch.span.start.index)
+                        .next()
+                });
+                ("IF", vec![then, else_keyword])
+            }
+            InstructionKind::Select { whens, otherwise } => {
+                let mut keywords = Vec::new();
+                for when in whens {
+                    let condition = when.condition.span();
+                    keywords.push(
+                        self.keywords("WHEN", when.span.start.index, condition.start.index)
+                            .next(),
+                    );
+                    keywords.push(
+                        self.keywords(
+                            "THEN",
+                            condition.end.index,
+                            when.instruction.span.start.index,
+                        )
+                        .next(),
+                    );
+                }
+                let mut end_start = whens
+                    .last()
+                    .map_or(span.start.index, |when| when.span.end.index);
+                if let Some(body) = otherwise {
+                    let body_start = body
+                        .first()
+                        .map_or(span.end.index, |instruction| instruction.span.start.index);
+                    keywords.push(self.keywords("OTHERWISE", end_start, body_start).next());
+                    if let Some(last) = body.last() {
+                        end_start = last.span.end.index;
+                    }
+                }
+                keywords.push(self.keywords("END", end_start, span.end.index).last());
+                ("SELECT", keywords)
+            }
+            InstructionKind::Do { body, end_name, .. } => {
+                let start = body
+                    .last()
+                    .map_or(span.start.index, |instruction| instruction.span.end.index);
+                let end = end_name
+                    .as_ref()
+                    .map_or(span.end.index, |name| name.range.start.index);
+                ("DO", vec![self.keywords("END", start, end).last()])
+            }
+            _ => return,
+        };
+        for keyword in keywords.into_iter().flatten() {
+            if self.contains(&keyword.range) {
+                self.found = Some(Target::Keyword {
+                    range: keyword.range.clone(),
+                    instruction: name.to_string(),
+                });
+            }
+        }
+    }
+}
+
+impl Visitor for Find<'_> {
+    fn visit_instruction(&mut self, instruction: &Instruction) {
+        match &instruction.kind {
+            InstructionKind::Label(token)
+            | InstructionKind::Signal(SignalTarget {
+                kind: SignalTargetKind::Label(token),
+                ..
+            }) if self.contains(&token.range) => {
+                self.found = Some(Target::Label(token.clone()));
+            }
+            InstructionKind::Label(_)
+            | InstructionKind::Assignment { .. }
+            | InstructionKind::Command(_)
+            | InstructionKind::Unknown(_) => {}
+            _ => {
+                let range = keyword_range(instruction, self.source);
+                if self.contains(&range) {
+                    let keyword = &self.source[range.start.index..range.end.index];
+                    self.found = Some(Target::Keyword {
+                        instruction: keyword.to_uppercase(),
+                        range,
+                    });
+                }
+            }
+        }
+        self.visit_clause_keywords(instruction);
+        walk_instruction(self, instruction);
+    }
+
+    fn visit_routine_name(&mut self, name: &RoutineName) {
+        let (RoutineName::Symbol(token) | RoutineName::Literal(token)) = name;
+        if self.contains(&token.range) {
+            self.found = Some(Target::Routine {
+                name: token.clone(),
+                literal: matches!(name, RoutineName::Literal(_)),
+            });
+        }
+    }
+}
+
+/// The range of the keyword that starts a keyword instruction.
+fn keyword_range(instruction: &Instruction, source: &str) -> lexer::Range {
+    let start = instruction.span.start.clone();
+    let length = instruction
+        .text(source)
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(instruction.span.end.index - start.index);
+    let mut end = start.clone();
+    end.character += length;
+    end.index += length;
+    lexer::Range { start, end }
+}
+
+/// Markdown for the keyword instruction, built-in function or label under
+/// the cursor. A call of a symbol that names a label of the program is a
+/// call of that label, even when a built-in function has the same name.
+pub fn hover(document: &Document, position: Position) -> Option<Hover> {
+    let source = document.file().text();
+    let mut find = Find {
+        source,
+        tokens: document.tokens(),
+        offset: document.lines.offset(position),
+        found: None,
+    };
+    find.visit_program(&document.file().program);
+    let target = find.found?;
+    let labels = document.labels();
+    let label = |token: &Token| {
+        let name = label_name(token, source);
+        labels
+            .labels
+            .iter()
+            .find(|label| label_name(label, source) == name)
+    };
+    let value = match &target {
+        Target::Keyword { instruction, .. } => docs::keyword(instruction)?.markdown(),
+        Target::Routine { name, literal } => match label(name).filter(|_| !literal) {
+            Some(label) => label_markdown(document, label),
+            None => docs::function(&label_name(name, source))?.markdown(),
+        },
+        Target::Label(token) => label_markdown(document, label(token)?),
+    };
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(document.lines.range(target.range())),
+    })
+}
+
+/// The label with the ARG or PARSE ARG that follows it, and the comments
+/// just above it.
+fn label_markdown(document: &Document, label: &Token) -> String {
+    let source = document.file().text();
+    let mut value = format!("```rexx\n{}:", label.text(source));
+    if let Some(arg) = arguments(&document.file().program.instructions, label) {
+        value.push_str(&format!("\n  {}", arg.text(source)));
+    }
+    value.push_str("\n```");
+    let comment = doc_comment(document, label);
+    if !comment.is_empty() {
+        value.push_str(&format!("\n\n{comment}"));
+    }
+    value
+}
+
+/// The ARG or PARSE ARG instruction after a label, if it comes before any
+/// other instruction but a PROCEDURE.
+fn arguments<'a>(instructions: &'a [Instruction], label: &Token) -> Option<&'a Instruction> {
+    let index = instructions.iter().position(
+        |instruction| matches!(&instruction.kind, InstructionKind::Label(name) if name == label),
+    );
+    let Some(index) = index else {
+        return instructions
+            .iter()
+            .flat_map(nested_instructions)
+            .find_map(|instructions| arguments(instructions, label));
+    };
+    instructions[index + 1..]
+        .iter()
+        .find(|instruction| {
+            !matches!(
+                instruction.kind,
+                InstructionKind::Label(_) | InstructionKind::Procedure { .. }
+            )
+        })
+        .filter(|instruction| {
+            matches!(
+                instruction.kind,
+                InstructionKind::Arg(_)
+                    | InstructionKind::Parse {
+                        source: ParseSource {
+                            kind: ParseSourceKind::Arg,
+                            ..
+                        },
+                        ..
+                    }
+            )
+        })
+}
+
+/// The lists of instructions directly inside an instruction. The branches
+/// of IF and WHEN are lists of one.
+fn nested_instructions(instruction: &Instruction) -> Vec<&[Instruction]> {
+    match &instruction.kind {
+        InstructionKind::Do { body, .. } => vec![body],
+        InstructionKind::If {
+            then_branch,
+            else_branch,
+            ..
+        } => std::iter::once(then_branch)
+            .chain(else_branch)
+            .map(|branch| slice::from_ref(&**branch))
+            .collect(),
+        InstructionKind::Select { whens, otherwise } => whens
+            .iter()
+            .map(|when| slice::from_ref(&when.instruction))
+            .chain(otherwise.as_deref())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The text of the comments on the lines just above a label, or before it
+/// on its own line. A blank line or a line with code ends them.
+fn doc_comment(document: &Document, label: &Token) -> String {
+    let source = document.file().text();
+    let tokens = document.tokens();
+    let before = tokens.partition_point(|token| token.range.start.index < label.range.start.index);
+    let mut comments = Vec::new();
+    let mut line_ends = 0;
+    for token in tokens[..before].iter().rev() {
+        match token.token_type {
+            TokenType::Whitespace => {}
+            TokenType::EOL if line_ends == 1 => break,
+            TokenType::EOL => line_ends += 1,
+            TokenType::Comment => {
+                comments.push(token);
+                line_ends = 0;
+            }
+            _ => {
+                // A comment after code on the same line belongs to the code.
+                if line_ends == 0 {
+                    comments.pop();
+                }
+                break;
+            }
+        }
+    }
+    comments
+        .iter()
+        .rev()
+        .flat_map(|comment| {
+            let text = comment.text(source);
+            let text = text.strip_prefix("/*").unwrap_or(text);
+            let text = text.strip_suffix("*/").unwrap_or(text);
+            text.lines()
+                .map(|line| line.trim().trim_start_matches('*').trim())
+                .collect::<Vec<_>>()
+        })
+        .skip_while(|line| line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "say total(2, 3) substr('abc', 2)\n\
+                          call 'TRANSLATE' x\n\
+                          exit\n\
+                          \n\
+                          /*\n \
+                          * Adds the rate to an amount.\n \
+                          */\n\
+                          Total: procedure\n\
+                          \x20 parse arg amount, rate\n\
+                          \x20 return amount + rate\n\
+                          say 1 /* not a doc comment */\n\
+                          done:\n\
+                          if x then nop\n\
+                          else do\n\
+                          \x20 /* Inside a group. */\n\
+                          \x20 inner: arg a\n\
+                          end\n\
+                          select\n\
+                          when x then nop\n\
+                          otherwise say 1\n\
+                          end\n";
+
+    fn hover_text(line: u32, character: u32) -> Option<String> {
+        let document = Document::new(1, SOURCE.to_string());
+        let hover = hover(&document, Position::new(line, character))?;
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("unexpected contents: {:?}", hover.contents);
+        };
+        Some(content.value)
+    }
+
+    #[test]
+    fn hover_keywords_and_functions() {
+        let say = hover_text(0, 1).unwrap();
+        assert!(say.starts_with("```rexx\nSAY [expression]\n```"), "{say}");
+        let substr = hover_text(0, 22).unwrap();
+        assert!(substr.starts_with("```rexx\nSUBSTR(string, n"), "{substr}");
+        let translate = hover_text(1, 8).unwrap();
+        assert!(translate.starts_with("```rexx\nTRANSLATE("), "{translate}");
+        assert!(hover_text(2, 2).unwrap().contains("EXIT [expression]"));
+        // The operands of an instruction and the blank after its keyword.
+        assert_eq!(hover_text(1, 17), None);
+        assert_eq!(hover_text(8, 1), None);
+    }
+
+    #[test]
+    fn hover_clause_keywords() {
+        let documented_by = |line, character, syntax: &str| {
+            let text = hover_text(line, character).unwrap();
+            assert!(text.starts_with(&format!("```rexx\n{syntax}")), "{text}");
+        };
+        documented_by(12, 6, "IF ");
+        documented_by(13, 2, "IF ");
+        documented_by(16, 1, "DO ");
+        documented_by(18, 0, "SELECT\n");
+        documented_by(18, 8, "SELECT\n");
+        documented_by(19, 4, "SELECT\n");
+        documented_by(20, 3, "SELECT\n");
+        // The condition of the WHEN and the instruction after OTHERWISE.
+        assert_eq!(hover_text(18, 5), None);
+        assert!(hover_text(19, 11).unwrap().contains("SAY [expression]"));
+    }
+
+    #[test]
+    fn hover_labels() {
+        let expected = "```rexx\nTotal:\n  parse arg amount, rate\n```\n\n\
+                        Adds the rate to an amount.";
+        assert_eq!(hover_text(0, 5).unwrap(), expected);
+        assert_eq!(hover_text(7, 2).unwrap(), expected);
+        assert_eq!(hover_text(11, 1).unwrap(), "```rexx\ndone:\n```");
+        assert_eq!(
+            hover_text(15, 3).unwrap(),
+            "```rexx\ninner:\n  arg a\n```\n\nInside a group."
+        );
+    }
+}
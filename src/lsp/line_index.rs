@@ -0,0 +1,152 @@
+use std::ops;
+use std::sync::Arc;
+
+use lsp_types::{Position, Range};
+
+/// Converts between byte offsets in a text and LSP positions, whose
+/// characters count UTF-16 code units. Lines end at `\n`, `\r\n` or `\r`,
+/// as in LSP.
+pub struct LineIndex {
+    text: Arc<str>,
+    /// The byte offset of the start of each line.
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(text: Arc<str>) -> LineIndex {
+        let mut line_starts = vec![0];
+        scan_lines(&text, 0..text.len(), &mut line_starts);
+        LineIndex { text, line_starts }
+    }
+
+    /// The text as a shared string.
+    pub fn shared_text(&self) -> Arc<str> {
+        self.text.clone()
+    }
+
+    /// Replaces a byte range of the text. Only the lines that the range
+    /// touches are scanned again, together with the line before it, whose
+    /// `\r` may now be followed by a `\n`.
+    pub fn replace(&mut self, range: ops::Range<usize>, replacement: &str) {
+        let mut text = String::with_capacity(self.text.len() - range.len() + replacement.len());
+        text.push_str(&self.text[..range.start]);
+        text.push_str(replacement);
+        text.push_str(&self.text[range.end..]);
+
+        let line = self
+            .line_starts
+            .partition_point(|&start| start <= range.start)
+            - 1;
+        let first = line.saturating_sub(1);
+        let next = self
+            .line_starts
+            .partition_point(|&start| start <= range.end);
+        let shift = |start: usize| start - range.len() + replacement.len();
+        let tail = self.line_starts[next..]
+            .iter()
+            .map(|&start| shift(start))
+            .collect::<Vec<_>>();
+        let end = tail.first().map_or(text.len(), |&start| start - 1);
+        self.line_starts.truncate(first + 1);
+        scan_lines(&text, self.line_starts[first]..end, &mut self.line_starts);
+        self.line_starts.extend(tail);
+        self.text = text.into();
+    }
+
+    /// The LSP position of a byte offset.
+    pub fn position(&self, offset: usize) -> Position {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        let character = self.text[start..offset].encode_utf16().count();
+        Position::new(line as u32, character as u32)
+    }
+
+    /// The LSP range of a token or node.
+    pub fn range(&self, range: &rexx_parser::lexer::Range) -> Range {
+        Range::new(
+            self.position(range.start.index),
+            self.position(range.end.index),
+        )
+    }
+
+    /// The byte offset of an LSP position. A position past the end of its
+    /// line is taken as the end of the line, and one past the last line as
+    /// the end of the text.
+    pub fn offset(&self, position: Position) -> usize {
+        let line = position.line as usize;
+        let Some(&start) = self.line_starts.get(line) else {
+            return self.text.len();
+        };
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.text.len(), |&next| next);
+        let content = self.text[start..end].trim_end_matches(['\n', '\r']);
+        let mut units = 0;
+        for (index, ch) in content.char_indices() {
+            if units >= position.character as usize {
+                return start + index;
+            }
+            units += ch.len_utf16();
+        }
+        start + content.len()
+    }
+}
+
+/// Adds the start of each line that begins after a line end in `range`.
+fn scan_lines(text: &str, range: ops::Range<usize>, line_starts: &mut Vec<usize>) {
+    let bytes = text.as_bytes();
+    for index in range {
+        match bytes[index] {
+            b'\n' => line_starts.push(index + 1),
+            b'\r' if bytes.get(index + 1) != Some(&b'\n') => line_starts.push(index + 1),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_utf16_positions() {
+        let text = "say 'é'\r\nx = '😀' y\rz\n";
+        let lines = LineIndex::new(text.into());
+        let y = text.find(" y").unwrap() + 1;
+        assert_eq!(lines.position(y), Position::new(1, 9));
+        assert_eq!(lines.offset(Position::new(1, 9)), y);
+        let z = text.find('z').unwrap();
+        assert_eq!(lines.position(z), Position::new(2, 0));
+        assert_eq!(lines.offset(Position::new(2, 0)), z);
+        let quote = text.find("'\r").unwrap();
+        assert_eq!(lines.position(quote), Position::new(0, 6));
+
+        // Past the end of a line or of the text.
+        assert_eq!(lines.offset(Position::new(0, 40)), text.find('\r').unwrap());
+        assert_eq!(lines.offset(Position::new(9, 0)), text.len());
+        assert_eq!(lines.position(text.len()), Position::new(3, 0));
+    }
+
+    #[test]
+    fn replace_text() {
+        let edits = [
+            ("a\rb\nc", 2..2, "\n"),
+            ("a\r\nb", 2..3, ""),
+            ("a\nb\nc\nd", 1..5, "x\ry"),
+            ("ab\ncd", 1..1, "\r"),
+            ("ab\ncd", 2..2, "\r"),
+            ("ab\r\ncd\n", 5..6, ""),
+            ("ab", 0..2, "1\n2\n"),
+        ];
+        for (text, range, replacement) in edits {
+            let mut lines = LineIndex::new(text.into());
+            lines.replace(range.clone(), replacement);
+            let mut expected = text.to_string();
+            expected.replace_range(range, replacement);
+            let fresh = LineIndex::new(expected.as_str().into());
+            assert_eq!(&*lines.text, expected);
+            assert_eq!(lines.line_starts, fresh.line_starts, "{expected:?}");
+        }
+    }
+}
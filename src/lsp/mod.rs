@@ -0,0 +1,385 @@
+mod diagnostics;
+mod docs;
+mod documents;
+mod hover;
+mod line_index;
+mod navigation;
+
+use lsp_server::{
+    Connection, ErrorCode, ExtractError, Message, Notification, Request, RequestId, Response,
+};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+        PublishDiagnostics,
+    },
+    request::{DocumentSymbolRequest, GotoDefinition, HoverRequest, References},
+    DocumentSymbolResponse, GotoDefinitionResponse, HoverProviderCapability, InitializeParams,
+    Location, OneOf, PublishDiagnosticsParams, ServerCapabilities, SymbolInformation, SymbolKind,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Uri,
+};
+use std::error::Error;
+
+use rexx_parser::ast::Spanned;
+use serde::Serialize;
+
+use diagnostics::Pending;
+use documents::{Document, Documents};
+
+pub fn run_lsp() -> Result<(), Box<dyn Error + Sync + Send>> {
+    let (connection, io_threads) = Connection::stdio();
+    let server_capabilities = serde_json::to_value(&ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..Default::default()
+    })
+    .unwrap();
+    let initialization_params = match connection.initialize(server_capabilities) {
+        Ok(it) => it,
+        Err(e) => {
+            if e.channel_is_disconnected() {
+                io_threads.join()?;
+            }
+            return Err(e.into());
+        }
+    };
+    lsp_loop(connection, initialization_params)?;
+    io_threads.join()?;
+    // Shut down gracefully.
+    eprintln!("Shutting down LSP server");
+    Ok(())
+}
+
+fn lsp_loop(
+    connection: Connection,
+    params: serde_json::Value,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let _params: InitializeParams = serde_json::from_value(params).unwrap();
+    let mut documents = Documents::default();
+    let mut pending = Pending::default();
+    eprintln!("starting example main loop");
+    loop {
+        // Wait for the next message, or until the diagnostics of a changed
+        // document are due.
+        let received = match pending.deadline() {
+            Some(deadline) => connection.receiver.recv_deadline(deadline),
+            None => connection.receiver.recv().map_err(Into::into),
+        };
+        let msg = match received {
+            Ok(msg) => msg,
+            Err(e) if e.is_timeout() => {
+                for uri in pending.take_due() {
+                    if let Some(document) = documents.get(&uri) {
+                        let params = diagnostics::publish_params(uri, document);
+                        publish_diagnostics(&connection, params)?;
+                    }
+                }
+                continue;
+            }
+            Err(_) => break,
+        };
+        match msg {
+            Message::Request(req) => {
+                if connection.handle_shutdown(&req)? {
+                    return Ok(());
+                }
+                eprintln!("got request: {}", req.method);
+                // Kept to answer requests whose parameters are invalid.
+                let id = req.id.clone();
+                let req = match cast::<DocumentSymbolRequest>(req) {
+                    Ok((id, params)) => {
+                        let uri = &params.text_document.uri;
+                        let result = documents
+                            .get(uri)
+                            .map(|document| document_symbols(uri, document));
+                        respond(&connection, id, result)?;
+                        continue;
+                    }
+                    Err(ExtractError::JsonError { error, .. }) => {
+                        respond_error(&connection, id, ErrorCode::InvalidParams, error)?;
+                        continue;
+                    }
+                    Err(ExtractError::MethodMismatch(req)) => req,
+                };
+                let req = match cast::<GotoDefinition>(req) {
+                    Ok((id, params)) => {
+                        let params = params.text_document_position_params;
+                        let uri = &params.text_document.uri;
+                        let result = documents.get(uri).and_then(|document| {
+                            navigation::definition(uri, document, params.position)
+                        });
+                        respond(&connection, id, result.map(GotoDefinitionResponse::Scalar))?;
+                        continue;
+                    }
+                    Err(ExtractError::JsonError { error, .. }) => {
+                        respond_error(&connection, id, ErrorCode::InvalidParams, error)?;
+                        continue;
+                    }
+                    Err(ExtractError::MethodMismatch(req)) => req,
+                };
+                let req = match cast::<References>(req) {
+                    Ok((id, params)) => {
+                        let position = params.text_document_position;
+                        let uri = &position.text_document.uri;
+                        let result = documents.get(uri).map(|document| {
+                            navigation::references(
+                                uri,
+                                document,
+                                position.position,
+                                params.context.include_declaration,
+                            )
+                        });
+                        respond(&connection, id, result)?;
+                        continue;
+                    }
+                    Err(ExtractError::JsonError { error, .. }) => {
+                        respond_error(&connection, id, ErrorCode::InvalidParams, error)?;
+                        continue;
+                    }
+                    Err(ExtractError::MethodMismatch(req)) => req,
+                };
+                let req = match cast::<HoverRequest>(req) {
+                    Ok((id, params)) => {
+                        let params = params.text_document_position_params;
+                        let uri = &params.text_document.uri;
+                        let result = documents
+                            .get(uri)
+                            .and_then(|document| hover::hover(document, params.position));
+                        respond(&connection, id, result)?;
+                        continue;
+                    }
+                    Err(ExtractError::JsonError { error, .. }) => {
+                        respond_error(&connection, id, ErrorCode::InvalidParams, error)?;
+                        continue;
+                    }
+                    Err(ExtractError::MethodMismatch(req)) => req,
+                };
+                let message = format!("unhandled method {}", req.method);
+                respond_error(&connection, req.id, ErrorCode::MethodNotFound, message)?;
+            }
+            Message::Response(resp) => {
+                eprintln!("got response: {}", resp.id);
+            }
+
+            Message::Notification(not) => {
+                eprintln!("got notification: {}", not.method);
+                let not = match cast_notification::<DidOpenTextDocument>(not) {
+                    Ok(params) => {
+                        pending.schedule(params.text_document.uri.clone());
+                        documents.open(params);
+                        continue;
+                    }
+                    Err(ExtractError::JsonError { method, error }) => {
+                        eprintln!("ignoring invalid {method} notification: {error}");
+                        continue;
+                    }
+                    Err(ExtractError::MethodMismatch(not)) => not,
+                };
+                let not = match cast_notification::<DidChangeTextDocument>(not) {
+                    Ok(params) => {
+                        pending.schedule(params.text_document.uri.clone());
+                        documents.change(params);
+                        continue;
+                    }
+                    Err(ExtractError::JsonError { method, error }) => {
+                        eprintln!("ignoring invalid {method} notification: {error}");
+                        continue;
+                    }
+                    Err(ExtractError::MethodMismatch(not)) => not,
+                };
+                match cast_notification::<DidCloseTextDocument>(not) {
+                    Ok(params) => {
+                        // Clear the diagnostics of the closed document.
+                        let uri = params.text_document.uri.clone();
+                        pending.cancel(&uri);
+                        documents.close(params);
+                        publish_diagnostics(
+                            &connection,
+                            PublishDiagnosticsParams::new(uri, Vec::new(), None),
+                        )?;
+                    }
+                    Err(ExtractError::JsonError { method, error }) => {
+                        eprintln!("ignoring invalid {method} notification: {error}");
+                    }
+                    Err(ExtractError::MethodMismatch(_)) => {}
+                };
+            }
+        }
+    }
+    Ok(())
+}
+
+fn respond(
+    connection: &Connection,
+    id: RequestId,
+    result: impl Serialize,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let resp = Response {
+        id,
+        result: Some(serde_json::to_value(&result).unwrap()),
+        error: None,
+    };
+    connection.sender.send(Message::Response(resp))?;
+    Ok(())
+}
+
+/// Answers a request with an error instead of a result.
+fn respond_error(
+    connection: &Connection,
+    id: RequestId,
+    code: ErrorCode,
+    message: impl ToString,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let resp = Response::new_err(id, code as i32, message.to_string());
+    connection.sender.send(Message::Response(resp))?;
+    Ok(())
+}
+
+fn publish_diagnostics(
+    connection: &Connection,
+    params: PublishDiagnosticsParams,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let not = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+    connection.sender.send(Message::Notification(not))?;
+    Ok(())
+}
+
+/// The labels of a file as a flat list of symbols.
+fn document_symbols(uri: &Uri, document: &Document) -> DocumentSymbolResponse {
+    let file = document.file();
+    #[allow(deprecated)]
+    DocumentSymbolResponse::Flat(
+        document
+            .labels()
+            .labels
+            .iter()
+            .map(|x| SymbolInformation {
+                name: x.text(file.text()).to_string(),
+                kind: SymbolKind::FUNCTION,
+                tags: None,
+                deprecated: None,
+                location: Location {
+                    uri: uri.clone(),
+                    range: document.lines.range(&x.range),
+                },
+                container_name: None,
+            })
+            .collect(),
+    )
+}
+
+fn cast<R>(req: Request) -> Result<(RequestId, R::Params), ExtractError<Request>>
+where
+    R: lsp_types::request::Request,
+    R::Params: serde::de::DeserializeOwned,
+{
+    req.extract(R::METHOD)
+}
+
+fn cast_notification<N>(not: Notification) -> Result<N::Params, ExtractError<Notification>>
+where
+    N: lsp_types::notification::Notification,
+    N::Params: serde::de::DeserializeOwned,
+{
+    not.extract(N::METHOD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::thread;
+
+    fn request(id: i32, method: &str, params: serde_json::Value) -> Message {
+        Message::Request(Request::new(id.into(), method.to_string(), params))
+    }
+
+    fn notification(method: &str, params: serde_json::Value) -> Message {
+        Message::Notification(Notification::new(method.to_string(), params))
+    }
+
+    #[test]
+    fn answer_invalid_and_unknown_requests() {
+        let (server, client) = Connection::memory();
+        let server = thread::spawn(move || {
+            lsp_loop(server, json!({ "capabilities": {} })).unwrap();
+        });
+        let messages = [
+            notification("textDocument/didOpen", json!({ "textDocument": 1 })),
+            request(1, "textDocument/hover", json!({ "position": "start" })),
+            request(2, "workspace/symbol", json!({ "query": "" })),
+            request(3, "shutdown", serde_json::Value::Null),
+            notification("exit", serde_json::Value::Null),
+        ];
+        for message in messages {
+            client.sender.send(message).unwrap();
+        }
+        let responses = client
+            .receiver
+            .iter()
+            .map(|message| match message {
+                Message::Response(resp) => (resp.id, resp.error.map(|error| error.code)),
+                message => panic!("unexpected message: {message:?}"),
+            })
+            .collect::<Vec<_>>();
+        server.join().unwrap();
+        assert_eq!(
+            responses,
+            [
+                (1.into(), Some(ErrorCode::InvalidParams as i32)),
+                (2.into(), Some(ErrorCode::MethodNotFound as i32)),
+                (3.into(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn answer_on_unterminated_label_names() {
+        let (server, client) = Connection::memory();
+        let server = thread::spawn(move || {
+            lsp_loop(server, json!({ "capabilities": {} })).unwrap();
+        });
+        let uri = "file:///tmp/unterminated.rexx";
+        let position = |id, method, line, character| {
+            let params = json!({
+                "textDocument": { "uri": uri },
+                "position": { "line": line, "character": character },
+            });
+            request(id, method, params)
+        };
+        let document = json!({
+            "uri": uri,
+            "languageId": "rexx",
+            "version": 1,
+            "text": "signal '\ncall on error name '\n",
+        });
+        let messages = [
+            notification("textDocument/didOpen", json!({ "textDocument": document })),
+            position(1, "textDocument/definition", 0, 7),
+            position(2, "textDocument/hover", 0, 7),
+            position(3, "textDocument/definition", 1, 19),
+            position(4, "textDocument/hover", 1, 0),
+            request(5, "shutdown", serde_json::Value::Null),
+            notification("exit", serde_json::Value::Null),
+        ];
+        for message in messages {
+            client.sender.send(message).unwrap();
+        }
+        let responses = client
+            .receiver
+            .iter()
+            .filter_map(|message| match message {
+                Message::Response(resp) => Some((resp.id, resp.error.is_none())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        server.join().unwrap();
+        let expected = (1..=5).map(|id| (id.into(), true)).collect::<Vec<_>>();
+        assert_eq!(responses, expected);
+    }
+}
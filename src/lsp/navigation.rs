@@ -0,0 +1,115 @@
+use lsp_types::{Location, Position, Uri};
+use rexx_parser::checks::{label_name, Labels};
+use rexx_parser::lexer::Token;
+
+use super::documents::Document;
+
+/// The name of the label or label reference under the cursor. The cursor
+/// may be just after the token.
+fn name_at(labels: &Labels, document: &Document, position: Position) -> Option<String> {
+    let offset = document.lines.offset(position);
+    labels
+        .labels
+        .iter()
+        .chain(labels.references())
+        .find(|token| token.range.start.index <= offset && offset <= token.range.end.index)
+        .map(|token| label_name(token, document.file().text()))
+}
+
+fn location(uri: &Uri, document: &Document, token: &Token) -> Location {
+    Location::new(uri.clone(), document.lines.range(&token.range))
+}
+
+/// The label that the label or reference under the cursor stands for. When
+/// a label is defined twice, the first one is the one that is used.
+pub fn definition(uri: &Uri, document: &Document, position: Position) -> Option<Location> {
+    let labels = document.labels();
+    let name = name_at(labels, document, position)?;
+    let text = document.file().text();
+    labels
+        .labels
+        .iter()
+        .find(|label| label_name(label, text) == name)
+        .map(|label| location(uri, document, label))
+}
+
+/// Every SIGNAL, trap, CALL and function call of the label under the
+/// cursor, and the labels themselves if asked.
+pub fn references(
+    uri: &Uri,
+    document: &Document,
+    position: Position,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let labels = document.labels();
+    let Some(name) = name_at(labels, document, position) else {
+        return Vec::new();
+    };
+    let text = document.file().text();
+    let declarations = labels.labels.iter().filter(|_| include_declaration);
+    let mut tokens = declarations
+        .chain(labels.references())
+        .filter(|token| label_name(token, text) == name)
+        .collect::<Vec<_>>();
+    tokens.sort_by_key(|token| token.range.start.index);
+    tokens
+        .into_iter()
+        .map(|token| location(uri, document, token))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "call Total 1\n\
+                          say total(2) + 'TOTAL'(3)\n\
+                          if x then signal 'TOTAL'\n\
+                          signal done\n\
+                          total: procedure\n\
+                          \x20 return arg(1)\n\
+                          done:\n\
+                          Total:\n\
+                          signal on error name done\n\
+                          call on halt\n\
+                          halt:";
+
+    fn lines(locations: &[Location]) -> Vec<(u32, u32)> {
+        locations
+            .iter()
+            .map(|location| (location.range.start.line, location.range.start.character))
+            .collect()
+    }
+
+    #[test]
+    fn go_to_label() {
+        let uri: Uri = "file:///tmp/labels.rexx".parse().unwrap();
+        let document = Document::new(1, SOURCE.to_string());
+        let definition = |line, character| {
+            definition(&uri, &document, Position::new(line, character))
+                .map(|location| (location.range.start.line, location.range.start.character))
+        };
+        assert_eq!(definition(0, 6), Some((4, 0)));
+        assert_eq!(definition(1, 9), Some((4, 0)));
+        assert_eq!(definition(2, 17), Some((4, 0)));
+        assert_eq!(definition(3, 11), Some((6, 0)));
+        assert_eq!(definition(7, 0), Some((4, 0)));
+        // A trap with NAME, and one that goes to the label of its condition.
+        assert_eq!(definition(8, 22), Some((6, 0)));
+        assert_eq!(definition(9, 9), Some((10, 0)));
+        // A string function name, a built-in function and a variable.
+        assert_eq!(definition(1, 16), None);
+        assert_eq!(definition(5, 10), None);
+        assert_eq!(definition(2, 3), None);
+    }
+
+    #[test]
+    fn find_label_references() {
+        let uri: Uri = "file:///tmp/labels.rexx".parse().unwrap();
+        let document = Document::new(1, SOURCE.to_string());
+        let found = references(&uri, &document, Position::new(4, 2), false);
+        assert_eq!(lines(&found), [(0, 5), (1, 4), (2, 17)]);
+        let found = references(&uri, &document, Position::new(3, 7), true);
+        assert_eq!(lines(&found), [(3, 7), (6, 0), (8, 21)]);
+    }
+}
@@ -1,11 +1,15 @@
 #![allow(clippy::print_stderr)]
-// use rexx_parser::parser::RexxParser;
-mod ast;
-mod lexer;
-mod parser;
 mod lsp;
 
-use clap::{Parser, Subcommand};
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use rexx_parser::diagnostics::Severity;
+use rexx_parser::lexer::LogicalLine;
+use rexx_parser::{Diagnostic, Lexer};
+use serde::Serialize;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -21,46 +25,194 @@ enum Commands {
         // Path or file to tokenize
         #[arg(short, long)]
         path: String,
+        // Debug output, a JSON array or JSON lines
+        #[arg(short, long, value_enum, default_value_t = Format::Debug)]
+        format: Format,
     },
     Outline {
         // Path or file to outline
         #[arg(short, long)]
         path: String,
+        // Debug output, a JSON array or JSON lines
+        #[arg(short, long, value_enum, default_value_t = Format::Debug)]
+        format: Format,
+    },
+    Ast {
+        // Path or file to parse
+        #[arg(short, long)]
+        path: String,
+        // Debug output, a JSON array or JSON lines
+        #[arg(short, long, value_enum, default_value_t = Format::Debug)]
+        format: Format,
+    },
+    Check {
+        // Paths or files to check
+        #[arg(required = true)]
+        paths: Vec<String>,
+        // Fail on warnings too
+        #[arg(short = 'W', long)]
+        deny_warnings: bool,
     },
 }
 
-fn main() {
+/// How the lexer, outline and ast subcommands print their results.
+/// Diagnostics always go to stderr.
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Rust debug output
+    Debug,
+    /// A JSON array with an object per file
+    Json,
+    /// A JSON object per line, each with the path of its file
+    Jsonl,
+}
+
+/// A logical line, an instruction or a program with the file it came from,
+/// as printed by `--format jsonl`.
+#[derive(Serialize)]
+struct Record<'a, T> {
+    path: &'a Path,
+    #[serde(flatten)]
+    item: &'a T,
+}
+
+fn main() -> ExitCode {
     let cli = Cli::parse();
+    let mut failed = false;
     match &cli.command {
-        Commands::Lexer { path } => {
-            for file in list_files(std::path::Path::new(path)) {
-                print_file_lexemes(file);
+        Commands::Lexer { path, format } => {
+            let mut files = Vec::new();
+            for file in list_files(Path::new(path)) {
+                let Some(lines) = file_lexemes(&file) else {
+                    failed = true;
+                    continue;
+                };
+                print_items(*format, &file, &lines);
+                if let Format::Json = format {
+                    files.push(serde_json::json!({ "path": file, "lines": lines }));
+                }
             }
+            print_json(*format, &files);
         }
-        Commands::Outline { path } => {
-            for file in list_files(std::path::Path::new(path)) {
-                println!("File: {}", file.display());
-                print_file_outline(file);
+        Commands::Outline { path, format } => {
+            let mut files = Vec::new();
+            for file in list_files(Path::new(path)) {
+                if let Format::Debug = format {
+                    println!("File: {}", file.display());
+                }
+                let Some(parse) = parse_file(&file) else {
+                    failed = true;
+                    continue;
+                };
+                let instructions = &parse.program.instructions;
+                print_items(*format, &file, instructions);
+                if let Format::Json = format {
+                    files.push(serde_json::json!({ "path": file, "instructions": instructions }));
+                }
+            }
+            print_json(*format, &files);
+        }
+        Commands::Ast { path, format } => {
+            let mut files = Vec::new();
+            for file in list_files(Path::new(path)) {
+                let Some(parse) = parse_file(&file) else {
+                    failed = true;
+                    continue;
+                };
+                print_items(*format, &file, std::slice::from_ref(&parse.program));
+                if let Format::Json = format {
+                    files.push(serde_json::json!({ "path": file, "program": parse.program }));
+                }
+            }
+            print_json(*format, &files);
+        }
+        Commands::Check {
+            paths,
+            deny_warnings,
+        } => {
+            let fail_at = if *deny_warnings {
+                Severity::Warning
+            } else {
+                Severity::Error
+            };
+            for path in paths {
+                for file in list_files(Path::new(path)) {
+                    failed |= check_file(&file, fail_at);
+                }
             }
         }
         Commands::Lsp => {
             // Note that  we must have our logging only write out to stderr.
             eprintln!("Starting REXX LSP server");
-            lsp::run_lsp();
+            if let Err(e) = lsp::run_lsp() {
+                eprintln!("LSP server error: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if failed {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Prints the diagnostics of a file as `file:line:col: severity [code]
+/// message`. Returns whether the file has a diagnostic at least as severe
+/// as `fail_at`, or could not be read.
+fn check_file(path: &Path, fail_at: Severity) -> bool {
+    let parse = match rexx_parser::parse_file(path) {
+        Ok(parse) => parse,
+        Err(e) => {
+            eprintln!("{}: {e}", path.display());
+            return true;
         }
+    };
+    for diagnostic in &parse.diagnostics {
+        println!("{}:{}", path.display(), diagnostic);
     }
+    parse
+        .diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity <= fail_at)
 }
 
+/// Prints the items of a file one per line, as debug output or JSON lines.
+/// Nothing is printed for `--format json`, which prints all files at the end.
+fn print_items<T: Serialize + Debug>(format: Format, path: &Path, items: &[T]) {
+    match format {
+        Format::Debug => items.iter().for_each(|x| println!("{:?}", x)),
+        Format::Json => {}
+        Format::Jsonl => items.iter().for_each(|item| {
+            println!("{}", serde_json::to_string(&Record { path, item }).unwrap())
+        }),
+    }
+}
 
-fn print_file_outline(path: std::path::PathBuf) {
-    let content = std::fs::read_to_string(path).unwrap();
-    let mut lexer = lexer::Lexer::new(&content);
-    let mut parser = parser::RexxParser::new(&mut lexer);
-    let result = parser.parse();
-    result.iter().for_each(|x| println!("{:?}", x));
+/// Prints the objects collected for the files with `--format json`.
+fn print_json(format: Format, files: &[serde_json::Value]) {
+    if let Format::Json = format {
+        println!("{}", serde_json::to_string_pretty(files).unwrap());
+    }
 }
 
-fn list_files(path: &std::path::Path) -> Vec<std::path::PathBuf> {
+/// Parses a file and prints its diagnostics. A file that cannot be read is
+/// reported as `file: error` and gives `None`.
+fn parse_file(path: &Path) -> Option<rexx_parser::SourceFile> {
+    let parse = match rexx_parser::parse_file(path) {
+        Ok(parse) => parse,
+        Err(e) => {
+            eprintln!("{}: {e}", path.display());
+            return None;
+        }
+    };
+    parse
+        .diagnostics
+        .iter()
+        .for_each(|x| eprintln!("{}:{}", path.display(), x));
+    Some(parse)
+}
+
+fn list_files(path: &Path) -> Vec<PathBuf> {
     let mut result = Vec::new();
 
     if path.is_dir() {
@@ -79,16 +231,28 @@ fn list_files(path: &std::path::Path) -> Vec<std::path::PathBuf> {
     result
 }
 
-fn print_file_lexemes(path: std::path::PathBuf) {
-    let content = std::fs::read_to_string(path).unwrap();
-    let mut lexer = lexer::Lexer::new(&content);
+/// Like `parse_file`, but only tokenizes the file.
+fn file_lexemes(path: &Path) -> Option<Vec<LogicalLine>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("{}: {e}", path.display());
+            return None;
+        }
+    };
+    let mut lexer = Lexer::new(&content);
     let result = lexer.tokenize();
-    result.iter().for_each(|x| println!("{:?}", x));
+    lexer
+        .diagnostics()
+        .iter()
+        .for_each(|x| eprintln!("{}:{}", path.display(), Diagnostic::from_lex_error(x)));
+    Some(result)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use rexx_parser::syntax::{self, AstNode};
+
     #[test]
     fn check_snippets() {
         use std::fs::File;
@@ -102,22 +266,8 @@ mod tests {
                 let mut file = File::open(entry.path()).unwrap();
                 let mut contents = String::new();
                 file.read_to_string(&mut contents).unwrap();
-                assert_eq!(contents, rebuilt_from_tokens(&contents));
-            }
-        }
-    }
-
-    fn rebuilt_from_tokens(contents: &str) -> String {
-        let mut lexer = lexer::Lexer::new(&contents);
-        let lines = lexer.tokenize();
-        let mut result = String::new();
-        for line in lines {
-            for token in line.tokens {
-                if token.token_type != lexer::TokenType::Unknown {
-                    result.push_str(lexer.get_text(&token));
-                }
+                assert_eq!(contents, syntax::parse(&contents).syntax().to_string());
             }
         }
-        result
     }
 }
@@ -0,0 +1,115 @@
+//! How the token stream splits into clauses, shared by the parser and the
+//! syntax tree builder so that both see the same clauses.
+
+use crate::lexer::{LogicalLine, Range, SymbolKind, Token, TokenType};
+
+/// The keywords that start a clause: those of the keyword instructions,
+/// the THEN, ELSE, WHEN and OTHERWISE clauses that are only valid inside IF
+/// and SELECT, and END.
+pub(crate) const KEYWORD_INSTRUCTIONS: &[&str] = &[
+    "ADDRESS",
+    "ARG",
+    "DO",
+    "IF",
+    "CALL",
+    "DROP",
+    "EXIT",
+    "INTERPRET",
+    "ITERATE",
+    "LEAVE",
+    "NOP",
+    "NUMERIC",
+    "OPTIONS",
+    "PARSE",
+    "PROCEDURE",
+    "PULL",
+    "PUSH",
+    "QUEUE",
+    "RETURN",
+    "SAY",
+    "SELECT",
+    "SIGNAL",
+    "TRACE",
+    "THEN",
+    "ELSE",
+    "WHEN",
+    "OTHERWISE",
+    "END",
+];
+
+/// What a clause is, as far as its first two tokens tell.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum ClauseStart {
+    /// A clause terminator, or the end of the source.
+    Null,
+    /// `symbol:`
+    Label,
+    /// `symbol = expression`, even when the symbol is a keyword.
+    Assignment,
+    /// One of the `KEYWORD_INSTRUCTIONS`, in uppercase.
+    Keyword(&'static str),
+    /// Any other clause, whose value is a command for the environment.
+    Command,
+}
+
+/// Flattens the logical lines into one token stream. A continuation comma
+/// together with the end of line that follows it becomes a single blank, as
+/// in the standard; the text of that blank starts with the comma. Every
+/// clause ends with an `EOL`, a `Semicolon` or the final `EOS`.
+pub(crate) fn clause_tokens(lines: Vec<LogicalLine>) -> Vec<Token> {
+    let mut result = Vec::new();
+    for line in lines {
+        let last = line.tokens.len() - 1;
+        let mut tokens = line.tokens.into_iter().enumerate().peekable();
+        while let Some((_, token)) = tokens.next() {
+            match token.token_type {
+                TokenType::Comma if is_continuation(tokens.peek(), last) => {
+                    let (_, eol) = tokens.next().unwrap();
+                    result.push(Token {
+                        token_type: TokenType::Whitespace,
+                        range: Range {
+                            start: token.range.start,
+                            end: eol.range.end,
+                        },
+                    });
+                }
+                _ => result.push(token),
+            }
+        }
+    }
+    result
+}
+
+fn is_continuation(next: Option<&(usize, Token)>, last: usize) -> bool {
+    matches!(next, Some((i, token)) if *i != last && token.token_type == TokenType::EOL)
+}
+
+/// The uppercase text of a simple symbol, used to match keywords. Any
+/// other token gives an empty string.
+pub(crate) fn keyword(source: &str, token: &Token) -> String {
+    match token.token_type {
+        TokenType::Symbol(SymbolKind::Simple) => {
+            source[token.range.start.index..token.range.end.index].to_uppercase()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Tells what the clause that starts with `first` is. `second` is the next
+/// token that is not a blank, if any. A symbol followed by `:` or `=` is a
+/// label or an assignment even when it is a keyword.
+pub(crate) fn clause_start(source: &str, first: &Token, second: Option<&Token>) -> ClauseStart {
+    let second = second.map(|token| &token.token_type);
+    match first.token_type {
+        TokenType::EOL | TokenType::Semicolon | TokenType::EOS => ClauseStart::Null,
+        TokenType::Symbol(_) if second == Some(&TokenType::Colon) => ClauseStart::Label,
+        TokenType::Symbol(_) if second == Some(&TokenType::Equal) => ClauseStart::Assignment,
+        _ => {
+            let keyword = keyword(source, first);
+            match KEYWORD_INSTRUCTIONS.iter().find(|known| **known == keyword) {
+                Some(known) => ClauseStart::Keyword(known),
+                None => ClauseStart::Command,
+            }
+        }
+    }
+}
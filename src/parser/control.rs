@@ -0,0 +1,632 @@
+use crate::ast::{
+    Conditional, ConditionalKind, Instruction, InstructionKind, Repetitor, RepetitorKind, When,
+};
+use crate::lexer::{Token, TokenType};
+
+use super::{ParseError, ParseResult, RexxParser};
+
+/// The keywords that end the expressions of a DO clause.
+const DO_KEYWORDS: &[&str] = &["TO", "BY", "FOR", "WHILE", "UNTIL"];
+
+impl RexxParser<'_> {
+    /// Parses a DO instruction after its keyword, up to and including the
+    /// matching END. Errors in the DO clause or after END are recorded, and
+    /// the group is still returned.
+    pub(super) fn parse_do(&mut self, keyword: Token) -> ParseResult<InstructionKind> {
+        let start = self.position;
+        let (repetitor, conditional) = match self.parse_do_clause() {
+            Ok(clause) => clause,
+            Err(error) => {
+                self.recover(error, start);
+                (None, None)
+            }
+        };
+
+        let (body, ended) = self.parse_until_end();
+        if !ended {
+            self.errors.push(ParseError::IncompleteDo(keyword));
+            return Ok(InstructionKind::Do {
+                repetitor,
+                conditional,
+                body,
+                end_name: None,
+            });
+        }
+
+        let variable = match repetitor.as_deref().map(|repetitor| &repetitor.kind) {
+            Some(RepetitorKind::Controlled { variable, .. }) => Some(variable),
+            _ => None,
+        };
+        let end_name = self.parse_end_name();
+        if let Some(found) = &end_name {
+            match variable {
+                Some(variable) if self.same_symbol(variable, found) => {}
+                Some(_) => self.errors.push(ParseError::EndNameMismatch {
+                    keyword: Box::new(keyword),
+                    found: found.clone(),
+                }),
+                None => self.errors.push(ParseError::UnexpectedEndName {
+                    keyword: Box::new(keyword),
+                    found: found.clone(),
+                }),
+            }
+        }
+        self.recover_clause_end();
+        Ok(InstructionKind::Do {
+            repetitor,
+            conditional,
+            body,
+            end_name,
+        })
+    }
+
+    /// The repetitor and conditional of a DO clause, up to its end.
+    fn parse_do_clause(&mut self) -> ParseResult<(Option<Box<Repetitor>>, Option<Conditional>)> {
+        let start = self.position;
+        let repetitor = self.parse_repetitor()?.map(|kind| Repetitor {
+            kind,
+            span: self.span_from(start),
+        });
+        let start = self.position;
+        let conditional = self.parse_conditional()?.map(|kind| Conditional {
+            kind,
+            span: self.span_from(start),
+        });
+        let token = self.peek_significant();
+        if DO_KEYWORDS.contains(&self.keyword(token).as_str()) {
+            return Err(ParseError::InvalidDoKeyword(token.clone()));
+        }
+        self.expect_clause_end()?;
+        Ok((repetitor.map(Box::new), conditional))
+    }
+
+    fn parse_repetitor(&mut self) -> ParseResult<Option<RepetitorKind>> {
+        if self.at_clause_end() {
+            return Ok(None);
+        }
+        if self.is_assignment() {
+            let (variable, start) = self.parse_assignment_parts(DO_KEYWORDS)?;
+            let (mut to, mut by, mut count) = (None, None, None);
+            loop {
+                let token = self.peek_significant();
+                let slot = match self.keyword(token).as_str() {
+                    "TO" => &mut to,
+                    "BY" => &mut by,
+                    "FOR" => &mut count,
+                    _ => break,
+                };
+                if slot.is_some() {
+                    return Err(ParseError::InvalidDoKeyword(token.clone()));
+                }
+                self.skip_whitespace();
+                self.advance();
+                *slot = Some(Box::new(self.parse_expression(DO_KEYWORDS)?));
+            }
+            return Ok(Some(RepetitorKind::Controlled {
+                variable,
+                start,
+                to,
+                by,
+                count,
+            }));
+        }
+        let keyword = self.keyword(self.peek_significant());
+        if keyword == "WHILE" || keyword == "UNTIL" {
+            return Ok(None);
+        }
+        // FOREVER is only a keyword when nothing but a conditional follows.
+        let next = self.peek_significant_nth(1);
+        if keyword == "FOREVER"
+            && (matches!(
+                next.token_type,
+                TokenType::EOL | TokenType::Semicolon | TokenType::EOS
+            ) || matches!(self.keyword(next).as_str(), "WHILE" | "UNTIL"))
+        {
+            self.skip_whitespace();
+            self.advance();
+            return Ok(Some(RepetitorKind::Forever));
+        }
+        Ok(Some(RepetitorKind::Count(
+            self.parse_expression(DO_KEYWORDS)?,
+        )))
+    }
+
+    fn parse_conditional(&mut self) -> ParseResult<Option<ConditionalKind>> {
+        let keyword = self.keyword(self.peek_significant());
+        if keyword != "WHILE" && keyword != "UNTIL" {
+            return Ok(None);
+        }
+        self.skip_whitespace();
+        self.advance();
+        let expression = self.parse_expression(DO_KEYWORDS)?;
+        Ok(Some(if keyword == "WHILE" {
+            ConditionalKind::While(expression)
+        } else {
+            ConditionalKind::Until(expression)
+        }))
+    }
+
+    /// Parses an IF instruction after its keyword. THEN may start a new
+    /// line, null clauses may come before ELSE, and an ELSE belongs to the
+    /// nearest IF without one.
+    pub(super) fn parse_if(&mut self, keyword: Token) -> ParseResult<InstructionKind> {
+        let condition = self.parse_expression(&["THEN"])?;
+        let then = self.expect_then(keyword)?;
+        let then_branch = self.parse_branch(then)?;
+        let else_branch = match self.skip_null_clauses_to("ELSE") {
+            Some(token) => Some(Box::new(self.parse_branch(token)?)),
+            None => None,
+        };
+        Ok(InstructionKind::If {
+            condition,
+            then_branch: Box::new(then_branch),
+            else_branch,
+        })
+    }
+
+    /// Parses a SELECT instruction after its keyword, up to and including
+    /// the matching END. A clause that does not belong in a SELECT is
+    /// recorded as an error and skipped.
+    pub(super) fn parse_select(&mut self, keyword: Token) -> ParseResult<InstructionKind> {
+        self.recover_clause_end();
+        let mut whens = Vec::new();
+        let mut otherwise = None;
+        // MissingWhen is reported once, at the first clause that is not WHEN.
+        let mut missing_when = false;
+        loop {
+            self.skip_null_clauses();
+            let token = self.peek_significant().clone();
+            if token.token_type == TokenType::EOS {
+                self.errors.push(ParseError::IncompleteSelect(keyword));
+                return Ok(InstructionKind::Select { whens, otherwise });
+            }
+            let start = self.position;
+            if self.at_keyword("WHEN") && otherwise.is_none() {
+                match self.parse_when(token) {
+                    Ok(when) => whens.push(when),
+                    Err(error) => self.recover(error, start),
+                }
+            } else if self.at_keyword("OTHERWISE") && !whens.is_empty() && otherwise.is_none() {
+                self.skip_whitespace();
+                self.advance();
+                let (body, ended) = self.parse_until_end();
+                otherwise = Some(body);
+                if !ended {
+                    self.errors.push(ParseError::IncompleteSelect(keyword));
+                    return Ok(InstructionKind::Select { whens, otherwise });
+                }
+            } else if self.at_keyword("END") {
+                if whens.is_empty() && !missing_when {
+                    self.errors.push(ParseError::MissingWhen {
+                        keyword: Box::new(keyword.clone()),
+                        found: token,
+                    });
+                }
+                break;
+            } else if whens.is_empty() && !missing_when {
+                missing_when = true;
+                let error = ParseError::MissingWhen {
+                    keyword: Box::new(keyword.clone()),
+                    found: token,
+                };
+                self.recover(error, start);
+            } else if whens.is_empty() {
+                self.skip_clause();
+            } else {
+                let error = ParseError::InvalidSelectClause {
+                    keyword: Box::new(keyword.clone()),
+                    found: token,
+                };
+                self.recover(error, start);
+            }
+        }
+        if let Some(found) = self.parse_end_name() {
+            self.errors.push(ParseError::SelectEndName {
+                keyword: Box::new(keyword),
+                found,
+            });
+        }
+        self.recover_clause_end();
+        Ok(InstructionKind::Select { whens, otherwise })
+    }
+
+    /// Parses `WHEN condition THEN instruction` in a SELECT.
+    fn parse_when(&mut self, keyword: Token) -> ParseResult<When> {
+        self.skip_whitespace();
+        let start = self.position;
+        self.advance();
+        let condition = self.parse_expression(&["THEN"])?;
+        let then = self.expect_then(keyword)?;
+        let instruction = self.parse_branch(then)?;
+        Ok(When {
+            condition,
+            instruction,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Consumes the THEN that follows the condition of an IF or a WHEN,
+    /// either in the same clause or at the start of a later one.
+    fn expect_then(&mut self, keyword: Token) -> ParseResult<Token> {
+        match self.skip_null_clauses_to("THEN") {
+            Some(then) => Ok(then),
+            None => Err(ParseError::MissingThen {
+                keyword: Box::new(keyword),
+                found: self.peek_significant().clone(),
+            }),
+        }
+    }
+
+    /// Parses the instruction after THEN or ELSE. The keyword ends a clause
+    /// by itself, so the instruction may be on the same line or a later one.
+    fn parse_branch(&mut self, keyword: Token) -> ParseResult<Instruction> {
+        let is_then = self.keyword(&keyword) == "THEN";
+        loop {
+            self.skip_null_clauses();
+            if self.peek_significant().token_type == TokenType::EOS {
+                return Err(if is_then {
+                    ParseError::IncompleteThen(keyword)
+                } else {
+                    ParseError::IncompleteElse(keyword)
+                });
+            }
+            if self.at_keyword("END") {
+                return Err(if is_then {
+                    ParseError::EndAfterThen(keyword)
+                } else {
+                    ParseError::EndAfterElse(keyword)
+                });
+            }
+            if let Some(instruction) = self.parse_instruction()? {
+                return Ok(instruction);
+            }
+        }
+    }
+
+    /// Consumes null clauses and then `keyword` when the next non-null
+    /// clause starts with it. Nothing is consumed otherwise, and a clause
+    /// such as `else = 1` is an assignment.
+    fn skip_null_clauses_to(&mut self, keyword: &str) -> Option<Token> {
+        let position = self.position;
+        self.skip_null_clauses();
+        if self.at_keyword(keyword) {
+            self.skip_whitespace();
+            return Some(self.advance());
+        }
+        self.position = position;
+        None
+    }
+
+    /// Parses instructions up to the `END` of a group, which is left for the
+    /// caller, and tells whether it was found before the source ended.
+    fn parse_until_end(&mut self) -> (Vec<Instruction>, bool) {
+        let mut body = Vec::new();
+        loop {
+            self.skip_null_clauses();
+            if self.peek_significant().token_type == TokenType::EOS {
+                return (body, false);
+            }
+            if self.at_keyword("END") {
+                return (body, true);
+            }
+            body.extend(self.parse_clause());
+        }
+    }
+
+    /// Consumes `END` and returns the symbol that follows it, if any.
+    fn parse_end_name(&mut self) -> Option<Token> {
+        self.skip_whitespace();
+        self.advance();
+        if matches!(self.peek_significant().token_type, TokenType::Symbol(_)) {
+            self.skip_whitespace();
+            return Some(self.advance());
+        }
+        None
+    }
+
+    /// Symbols are compared without regard to case.
+    pub(super) fn same_symbol(&self, a: &Token, b: &Token) -> bool {
+        self.get_text(a).eq_ignore_ascii_case(self.get_text(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{InstructionKind, Program};
+    use crate::parser::kinds;
+
+    fn parse(source: &str) -> ParseResult<Program> {
+        let mut parser = RexxParser::new(source);
+        parser.parse()
+    }
+
+    #[test]
+    fn parse_simple_do() {
+        let program = parse("do\n  say 1; say 2\nend").unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [InstructionKind::Do {
+                repetitor: None,
+                conditional: None,
+                body,
+                end_name: None,
+            }] if body.len() == 2
+        ));
+    }
+
+    #[test]
+    fn parse_repetitive_do() {
+        let program = parse("do 5; end; do forever; end; do forever while x; end").unwrap();
+        let repetitors = kinds(&program.instructions)
+            .into_iter()
+            .map(|kind| match kind {
+                InstructionKind::Do {
+                    repetitor,
+                    conditional,
+                    ..
+                } => (repetitor.as_deref(), conditional.as_ref()),
+                _ => panic!("unexpected instruction: {kind:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            &repetitors[..],
+            [
+                (
+                    Some(Repetitor {
+                        kind: RepetitorKind::Count(_),
+                        ..
+                    }),
+                    None
+                ),
+                (
+                    Some(Repetitor {
+                        kind: RepetitorKind::Forever,
+                        ..
+                    }),
+                    None
+                ),
+                (
+                    Some(Repetitor {
+                        kind: RepetitorKind::Forever,
+                        ..
+                    }),
+                    Some(Conditional {
+                        kind: ConditionalKind::While(_),
+                        ..
+                    })
+                ),
+            ]
+        ));
+    }
+
+    #[test]
+    fn parse_controlled_do() {
+        let program = parse("do i = 1 to n by 2 for 10 until i > 5\n  say i\nend I").unwrap();
+        let instructions = kinds(&program.instructions);
+        let [InstructionKind::Do {
+            repetitor: Some(repetitor),
+            conditional:
+                Some(Conditional {
+                    kind: ConditionalKind::Until(_),
+                    ..
+                }),
+            body,
+            end_name: Some(_),
+        }] = &instructions[..]
+        else {
+            panic!("unexpected instructions: {:?}", program.instructions);
+        };
+        assert!(matches!(
+            **repetitor,
+            Repetitor {
+                kind: RepetitorKind::Controlled {
+                    to: Some(_),
+                    by: Some(_),
+                    count: Some(_),
+                    ..
+                },
+                ..
+            }
+        ));
+        assert_eq!(body.len(), 1);
+        let program = parse("do i = 1 for 3 to 9; end").unwrap();
+        let instructions = kinds(&program.instructions);
+        let [InstructionKind::Do {
+            repetitor: Some(repetitor),
+            ..
+        }] = &instructions[..]
+        else {
+            panic!("unexpected instructions: {:?}", program.instructions);
+        };
+        assert!(matches!(
+            **repetitor,
+            Repetitor {
+                kind: RepetitorKind::Controlled {
+                    to: Some(_),
+                    by: None,
+                    count: Some(_),
+                    ..
+                },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_nested_do() {
+        let program = parse("do while a\n do until b\n end = 1\n end\nend").unwrap();
+        let instructions = kinds(&program.instructions);
+        let [InstructionKind::Do { body, .. }] = &instructions[..] else {
+            panic!("unexpected instructions: {:?}", program.instructions);
+        };
+        assert!(matches!(
+            &kinds(body)[..],
+            [InstructionKind::Do { body, .. }]
+                if matches!(&kinds(body)[..], [InstructionKind::Assignment { .. }])
+        ));
+    }
+
+    #[test]
+    fn parse_do_errors() {
+        assert!(matches!(
+            parse("do i = 1 to 3\nsay i"),
+            Err(ParseError::IncompleteDo(_))
+        ));
+        assert!(matches!(
+            parse("say 1\nend"),
+            Err(ParseError::UnmatchedEnd(_))
+        ));
+        assert!(matches!(
+            parse("do i = 1 to 3; end j"),
+            Err(ParseError::EndNameMismatch { .. })
+        ));
+        assert!(matches!(
+            parse("do 3; end i"),
+            Err(ParseError::UnexpectedEndName { .. })
+        ));
+        assert!(matches!(
+            parse("do i = 1 to 3 to 4; end"),
+            Err(ParseError::InvalidDoKeyword(_))
+        ));
+        assert!(matches!(
+            parse("do 3 by 4; end"),
+            Err(ParseError::InvalidDoKeyword(_))
+        ));
+    }
+
+    #[test]
+    fn parse_if() {
+        let program = parse("if a = 1 then say 'one'; else say 'other'").unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [InstructionKind::If {
+                then_branch,
+                else_branch: Some(else_branch),
+                ..
+            }] if matches!(then_branch.kind, InstructionKind::Say(_))
+                && matches!(else_branch.kind, InstructionKind::Say(_))
+        ));
+    }
+
+    #[test]
+    fn parse_if_clauses() {
+        // THEN on the next line, a null clause before ELSE and a NOP body
+        let program = parse("if a\n  then\n    nop\n\n  else do\n    say b\n  end\nsay c").unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [
+                InstructionKind::If {
+                    then_branch,
+                    else_branch: Some(else_branch),
+                    ..
+                },
+                InstructionKind::Say(_),
+            ] if matches!(then_branch.kind, InstructionKind::Nop)
+                && matches!(else_branch.kind, InstructionKind::Do { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_dangling_else() {
+        let program = parse("if a then if b then say 1; else say 2").unwrap();
+        let instructions = kinds(&program.instructions);
+        let [InstructionKind::If {
+            then_branch,
+            else_branch: None,
+            ..
+        }] = &instructions[..]
+        else {
+            panic!("unexpected instructions: {:?}", program.instructions);
+        };
+        assert!(matches!(
+            then_branch.kind,
+            InstructionKind::If {
+                else_branch: Some(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_else_assignment() {
+        let program = parse("if a then nop\nelse = 1").unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [
+                InstructionKind::If {
+                    else_branch: None,
+                    ..
+                },
+                InstructionKind::Assignment { .. },
+            ]
+        ));
+    }
+
+    #[test]
+    fn parse_select() {
+        let source = "select\n  when a then say 1\n  when b\n    then nop\n  otherwise\n    say 3; say 4\nend";
+        let program = parse(source).unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [InstructionKind::Select {
+                whens,
+                otherwise: Some(otherwise),
+            }] if whens.len() == 2
+                && matches!(whens[1].instruction.kind, InstructionKind::Nop)
+                && otherwise.len() == 2
+        ));
+    }
+
+    #[test]
+    fn parse_if_and_select_errors() {
+        assert!(matches!(
+            parse("then say 1"),
+            Err(ParseError::UnexpectedThen(_))
+        ));
+        assert!(matches!(
+            parse("say 1; else say 2"),
+            Err(ParseError::UnexpectedElse(_))
+        ));
+        assert!(matches!(
+            parse("when a then nop"),
+            Err(ParseError::UnexpectedWhen(_))
+        ));
+        assert!(matches!(
+            parse("otherwise"),
+            Err(ParseError::UnexpectedOtherwise(_))
+        ));
+        assert!(matches!(
+            parse("if a; say 1"),
+            Err(ParseError::MissingThen { .. })
+        ));
+        assert!(matches!(
+            parse("if a then"),
+            Err(ParseError::IncompleteThen(_))
+        ));
+        assert!(matches!(
+            parse("if a then nop; else"),
+            Err(ParseError::IncompleteElse(_))
+        ));
+        assert!(matches!(
+            parse("do; if a then end"),
+            Err(ParseError::EndAfterThen(_))
+        ));
+        assert!(matches!(
+            parse("select; say 1; end"),
+            Err(ParseError::MissingWhen { .. })
+        ));
+        assert!(matches!(
+            parse("select; when a then nop; say 1; end"),
+            Err(ParseError::InvalidSelectClause { .. })
+        ));
+        assert!(matches!(
+            parse("select; when a then nop"),
+            Err(ParseError::IncompleteSelect(_))
+        ));
+        assert!(matches!(
+            parse("select; when a then nop; end x"),
+            Err(ParseError::SelectEndName { .. })
+        ));
+    }
+}
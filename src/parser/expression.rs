@@ -0,0 +1,393 @@
+use crate::ast::{BinaryOperator, Expression, ExpressionKind, PrefixOperator, RoutineName};
+use crate::lexer::{Token, TokenType};
+
+use super::{ParseError, ParseResult, RexxParser};
+
+// Priorities of the binary operators, as in the standard. All of them are
+// left associative, including `**`.
+const OR_PRIORITY: u8 = 1;
+const AND_PRIORITY: u8 = 2;
+const COMPARISON_PRIORITY: u8 = 3;
+pub(crate) const CONCATENATION_PRIORITY: u8 = 4;
+const ADDITIVE_PRIORITY: u8 = 5;
+const MULTIPLICATIVE_PRIORITY: u8 = 6;
+const POWER_PRIORITY: u8 = 7;
+
+impl RexxParser<'_> {
+    /// Parses an expression that ends at the end of the clause, at a comma,
+    /// at an unmatched `)` or at one of the `terminators` keywords (such as
+    /// `THEN` in an IF instruction).
+    pub(super) fn parse_expression(
+        &mut self,
+        terminators: &'static [&'static str],
+    ) -> ParseResult<Expression> {
+        let saved = std::mem::replace(&mut self.terminators, terminators);
+        let result = self.parse_binary(OR_PRIORITY);
+        self.terminators = saved;
+        result
+    }
+
+    /// Like `parse_expression`, but the expression may be absent.
+    pub(super) fn parse_optional_expression(
+        &mut self,
+        terminators: &'static [&'static str],
+    ) -> ParseResult<Option<Expression>> {
+        let token = self.peek_significant();
+        let absent = matches!(
+            token.token_type,
+            TokenType::EOL
+                | TokenType::Semicolon
+                | TokenType::EOS
+                | TokenType::Comma
+                | TokenType::RightParen
+        ) || terminators.contains(&self.keyword(token).as_str());
+        if absent {
+            return Ok(None);
+        }
+        self.parse_expression(terminators).map(Some)
+    }
+
+    fn parse_binary(&mut self, min_priority: u8) -> ParseResult<Expression> {
+        let start = self.position;
+        let mut left = self.parse_prefix()?;
+        while let Some((operator, priority)) = self.peek_binary_operator() {
+            if priority < min_priority {
+                break;
+            }
+            self.skip_whitespace();
+            // Blank and abuttal concatenation have no operator token.
+            if !matches!(
+                operator,
+                BinaryOperator::BlankConcatenate | BinaryOperator::AbuttalConcatenate
+            ) {
+                self.advance();
+            }
+            let right = self.parse_binary(priority + 1)?;
+            left = Expression {
+                kind: ExpressionKind::Binary {
+                    operator,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+                span: self.span_from(start),
+            };
+        }
+        Ok(left)
+    }
+
+    fn peek_binary_operator(&self) -> Option<(BinaryOperator, u8)> {
+        let token = self.peek_significant();
+        if let Some(operator) = binary_operator(&token.token_type) {
+            return Some(operator);
+        }
+        if self.starts_term(token) {
+            let operator = if self.peek().token_type == TokenType::Whitespace {
+                BinaryOperator::BlankConcatenate
+            } else {
+                BinaryOperator::AbuttalConcatenate
+            };
+            return Some((operator, CONCATENATION_PRIORITY));
+        }
+        None
+    }
+
+    fn starts_term(&self, token: &Token) -> bool {
+        match token.token_type {
+            TokenType::Symbol(_) => !self.terminators.contains(&self.keyword(token).as_str()),
+            TokenType::Literal
+            | TokenType::HexString
+            | TokenType::BinaryString
+            | TokenType::LeftParen => true,
+            _ => false,
+        }
+    }
+
+    fn parse_prefix(&mut self) -> ParseResult<Expression> {
+        self.skip_whitespace();
+        let start = self.position;
+        let operator = match self.peek().token_type {
+            TokenType::Plus => PrefixOperator::Plus,
+            TokenType::Minus => PrefixOperator::Minus,
+            TokenType::Not => PrefixOperator::Not,
+            _ => return self.parse_term(),
+        };
+        self.advance();
+        let operand = self.parse_prefix()?;
+        Ok(Expression {
+            kind: ExpressionKind::Prefix {
+                operator,
+                operand: Box::new(operand),
+            },
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_term(&mut self) -> ParseResult<Expression> {
+        self.skip_whitespace();
+        let start = self.position;
+        let token = self.peek().clone();
+        if !self.starts_term(&token) {
+            return Err(match token.token_type {
+                TokenType::Comma => ParseError::UnexpectedComma(token),
+                _ => ParseError::InvalidExpression(token),
+            });
+        }
+        self.advance();
+        let kind = match token.token_type {
+            TokenType::LeftParen => {
+                let expression = self.parse_parenthesized(token)?;
+                ExpressionKind::Parenthesized(Box::new(expression))
+            }
+            // A function call needs the `(` right after its name.
+            TokenType::Symbol(_) | TokenType::Literal
+                if self.peek().token_type == TokenType::LeftParen =>
+            {
+                let open = self.advance();
+                let args = self.parse_arguments(open)?;
+                let name = if token.token_type == TokenType::Literal {
+                    RoutineName::Literal(token)
+                } else {
+                    RoutineName::Symbol(token)
+                };
+                ExpressionKind::FunctionCall { name, args }
+            }
+            TokenType::Symbol(_) => ExpressionKind::Symbol(token),
+            _ => ExpressionKind::Literal(token),
+        };
+        Ok(Expression {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses the expression and the `)` that follow an opening `(`.
+    pub(super) fn parse_parenthesized(&mut self, open: Token) -> ParseResult<Expression> {
+        let saved = std::mem::take(&mut self.terminators);
+        let expression = self.parse_binary(OR_PRIORITY);
+        self.terminators = saved;
+        let expression = expression?;
+        self.skip_whitespace();
+        if self.peek().token_type != TokenType::RightParen {
+            return Err(self.unmatched_parenthesis(open));
+        }
+        self.advance();
+        Ok(expression)
+    }
+
+    /// Parses the arguments of a function call after its `(`. Omitted
+    /// arguments, as in `f(a,,c)`, are `None`.
+    fn parse_arguments(&mut self, open: Token) -> ParseResult<Vec<Option<Expression>>> {
+        let saved = std::mem::take(&mut self.terminators);
+        let result = self.parse_argument_list(open);
+        self.terminators = saved;
+        result
+    }
+
+    fn parse_argument_list(&mut self, open: Token) -> ParseResult<Vec<Option<Expression>>> {
+        let mut args = Vec::new();
+        self.skip_whitespace();
+        if self.peek().token_type == TokenType::RightParen {
+            self.advance();
+            return Ok(args);
+        }
+        loop {
+            self.skip_whitespace();
+            let arg = match self.peek().token_type {
+                TokenType::Comma | TokenType::RightParen => None,
+                _ => Some(self.parse_binary(OR_PRIORITY)?),
+            };
+            args.push(arg);
+            self.skip_whitespace();
+            match self.peek().token_type {
+                TokenType::Comma => {
+                    self.advance();
+                }
+                TokenType::RightParen => {
+                    self.advance();
+                    return Ok(args);
+                }
+                _ => return Err(self.unmatched_parenthesis(open)),
+            }
+        }
+    }
+
+    /// The error for a `(` whose `)` is missing. A token that cannot
+    /// continue the expression is reported instead when the clause goes on.
+    fn unmatched_parenthesis(&self, open: Token) -> ParseError {
+        let token = self.peek_significant().clone();
+        match token.token_type {
+            TokenType::EOL | TokenType::Semicolon | TokenType::EOS => {
+                ParseError::UnmatchedParenthesis(open)
+            }
+            TokenType::Comma => ParseError::UnexpectedComma(token),
+            _ => ParseError::InvalidExpression(token),
+        }
+    }
+}
+
+/// The binary operator that a token stands for, with its priority. The
+/// syntax tree builder nests expressions by the same priorities.
+pub(crate) fn binary_operator(token_type: &TokenType) -> Option<(BinaryOperator, u8)> {
+    let operator = match token_type {
+        TokenType::Power => (BinaryOperator::Power, POWER_PRIORITY),
+        TokenType::Multiply => (BinaryOperator::Multiply, MULTIPLICATIVE_PRIORITY),
+        TokenType::Divide => (BinaryOperator::Divide, MULTIPLICATIVE_PRIORITY),
+        TokenType::IntegerDivide => (BinaryOperator::IntegerDivide, MULTIPLICATIVE_PRIORITY),
+        TokenType::Remainder => (BinaryOperator::Remainder, MULTIPLICATIVE_PRIORITY),
+        TokenType::Plus => (BinaryOperator::Add, ADDITIVE_PRIORITY),
+        TokenType::Minus => (BinaryOperator::Subtract, ADDITIVE_PRIORITY),
+        TokenType::Concatenate => (BinaryOperator::Concatenate, CONCATENATION_PRIORITY),
+        TokenType::Equal => (BinaryOperator::Equal, COMPARISON_PRIORITY),
+        TokenType::NotEqual => (BinaryOperator::NotEqual, COMPARISON_PRIORITY),
+        TokenType::Greater => (BinaryOperator::Greater, COMPARISON_PRIORITY),
+        TokenType::Less => (BinaryOperator::Less, COMPARISON_PRIORITY),
+        TokenType::GreaterOrEqual => (BinaryOperator::GreaterOrEqual, COMPARISON_PRIORITY),
+        TokenType::LessOrEqual => (BinaryOperator::LessOrEqual, COMPARISON_PRIORITY),
+        TokenType::NotGreater => (BinaryOperator::NotGreater, COMPARISON_PRIORITY),
+        TokenType::NotLess => (BinaryOperator::NotLess, COMPARISON_PRIORITY),
+        TokenType::StrictEqual => (BinaryOperator::StrictEqual, COMPARISON_PRIORITY),
+        TokenType::StrictNotEqual => (BinaryOperator::StrictNotEqual, COMPARISON_PRIORITY),
+        TokenType::StrictGreater => (BinaryOperator::StrictGreater, COMPARISON_PRIORITY),
+        TokenType::StrictLess => (BinaryOperator::StrictLess, COMPARISON_PRIORITY),
+        TokenType::StrictGreaterOrEqual => {
+            (BinaryOperator::StrictGreaterOrEqual, COMPARISON_PRIORITY)
+        }
+        TokenType::StrictLessOrEqual => (BinaryOperator::StrictLessOrEqual, COMPARISON_PRIORITY),
+        TokenType::StrictNotGreater => (BinaryOperator::StrictNotGreater, COMPARISON_PRIORITY),
+        TokenType::StrictNotLess => (BinaryOperator::StrictNotLess, COMPARISON_PRIORITY),
+        TokenType::And => (BinaryOperator::And, AND_PRIORITY),
+        TokenType::Or => (BinaryOperator::Or, OR_PRIORITY),
+        TokenType::Xor => (BinaryOperator::Xor, OR_PRIORITY),
+        _ => return None,
+    };
+    Some(operator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::InstructionKind;
+    use crate::parser::kinds;
+
+    /// Parses `say <source>` and prints the expression as an S-expression.
+    fn parse(source: &str) -> ParseResult<String> {
+        let source = format!("say {source}");
+        let mut parser = RexxParser::new(&source);
+        let program = parser.parse()?;
+        match &kinds(&program.instructions)[..] {
+            [InstructionKind::Say(Some(expression))] => Ok(print(&parser, expression)),
+            other => panic!("unexpected instructions: {other:?}"),
+        }
+    }
+
+    fn print(parser: &RexxParser, expression: &Expression) -> String {
+        match &expression.kind {
+            ExpressionKind::Literal(token) | ExpressionKind::Symbol(token) => {
+                parser.get_text(token).to_string()
+            }
+            ExpressionKind::FunctionCall { name, args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| match arg {
+                        Some(arg) => print(parser, arg),
+                        None => "_".to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                let name = match name {
+                    RoutineName::Symbol(token) => parser.get_text(token).to_string(),
+                    RoutineName::Literal(token) => format!("L{}", parser.get_text(token)),
+                };
+                format!("{}({})", name, args.join(" "))
+            }
+            ExpressionKind::Parenthesized(inner) => format!("[{}]", print(parser, inner)),
+            ExpressionKind::Prefix { operator, operand } => {
+                format!("({:?} {})", operator, print(parser, operand))
+            }
+            ExpressionKind::Binary {
+                operator,
+                left,
+                right,
+            } => format!(
+                "({:?} {} {})",
+                operator,
+                print(parser, left),
+                print(parser, right)
+            ),
+        }
+    }
+
+    #[test]
+    fn parse_precedence() {
+        assert_eq!(
+            parse("1 + 2 * 3 ** 2").unwrap(),
+            "(Add 1 (Multiply 2 (Power 3 2)))"
+        );
+        assert_eq!(parse("2 ** 3 ** 2").unwrap(), "(Power (Power 2 3) 2)");
+        assert_eq!(parse("-2 ** 2").unwrap(), "(Power (Minus 2) 2)");
+        assert_eq!(parse("a - b - c").unwrap(), "(Subtract (Subtract a b) c)");
+        assert_eq!(
+            parse("a = 1 | b & \\c").unwrap(),
+            "(Or (Equal a 1) (And b (Not c)))"
+        );
+        assert_eq!(
+            parse("a < b && c >= d").unwrap(),
+            "(Xor (Less a b) (GreaterOrEqual c d))"
+        );
+        assert_eq!(parse("(1 + 2) * 3").unwrap(), "(Multiply [(Add 1 2)] 3)");
+    }
+
+    #[test]
+    fn parse_concatenation() {
+        assert_eq!(
+            parse("'a' b || c").unwrap(),
+            "(Concatenate (BlankConcatenate 'a' b) c)"
+        );
+        assert_eq!(
+            parse("'x'y'z'").unwrap(),
+            "(AbuttalConcatenate (AbuttalConcatenate 'x' y) 'z')"
+        );
+        // Concatenation binds weaker than addition and stronger than comparison.
+        assert_eq!(
+            parse("a b + 1 = c").unwrap(),
+            "(Equal (BlankConcatenate a (Add b 1)) c)"
+        );
+        assert_eq!(parse("a (b)").unwrap(), "(BlankConcatenate a [b])");
+        assert_eq!(parse("a /* c */ b").unwrap(), "(BlankConcatenate a b)");
+        assert_eq!(parse("a,\n b").unwrap(), "(BlankConcatenate a b)");
+    }
+
+    #[test]
+    fn parse_function_calls() {
+        assert_eq!(
+            parse("substr(x, 1, n + 1)").unwrap(),
+            "substr(x 1 (Add n 1))"
+        );
+        assert_eq!(
+            parse("f(a,,c) 'F'()").unwrap(),
+            "(BlankConcatenate f(a _ c) L'F'())"
+        );
+        assert_eq!(parse("f(,)").unwrap(), "f(_ _)");
+        assert_eq!(parse("f(g(1),)").unwrap(), "f(g(1) _)");
+    }
+
+    #[test]
+    fn parse_expression_errors() {
+        assert!(matches!(
+            parse("(1 + 2"),
+            Err(ParseError::UnmatchedParenthesis(_))
+        ));
+        assert!(matches!(
+            parse("f(1"),
+            Err(ParseError::UnmatchedParenthesis(_))
+        ));
+        assert!(matches!(
+            parse("1 +"),
+            Err(ParseError::InvalidExpression(_))
+        ));
+        assert!(matches!(parse("1, 2"), Err(ParseError::UnexpectedComma(_))));
+        assert!(matches!(
+            parse("1 + 2)"),
+            Err(ParseError::UnexpectedParenthesis(_))
+        ));
+    }
+}
@@ -0,0 +1,492 @@
+use crate::ast::{
+    AddressTarget, AddressTargetKind, Expression, InstructionKind, NumericForm, NumericFormKind,
+    NumericSetting, NumericSettingKind, TraceSetting, TraceSettingKind, VariableReference,
+    VariableReferenceKind,
+};
+use crate::lexer::{SymbolKind, Token, TokenType};
+
+use super::template::is_variable;
+use super::{ParseError, ParseResult, RexxParser};
+
+/// The letters that a TRACE setting may start with, after any `?`.
+const TRACE_LETTERS: &str = "ACEFILNOR";
+
+impl RexxParser<'_> {
+    /// Parses the expression that makes up the rest of the clause.
+    pub(super) fn parse_operand(&mut self) -> ParseResult<Expression> {
+        let expression = self.parse_expression(&[])?;
+        self.expect_clause_end()?;
+        Ok(expression)
+    }
+
+    /// Like `parse_operand`, but the expression may be absent.
+    pub(super) fn parse_optional_operand(&mut self) -> ParseResult<Option<Expression>> {
+        let expression = self.parse_optional_expression(&[])?;
+        self.expect_clause_end()?;
+        Ok(expression)
+    }
+
+    /// Parses an ADDRESS instruction after its keyword. Without VALUE, an
+    /// expression is only recognised when it does not start with a symbol or
+    /// a string, as those name an environment.
+    pub(super) fn parse_address(&mut self) -> ParseResult<AddressTarget> {
+        let start = self.position;
+        let token = self.peek_significant().clone();
+        let kind = if self.keyword(&token) == "VALUE" {
+            self.skip_whitespace();
+            self.advance();
+            AddressTargetKind::Value(self.parse_expression(&[])?)
+        } else {
+            match token.token_type {
+                _ if self.at_clause_end() => AddressTargetKind::Swap,
+                TokenType::Symbol(_) | TokenType::Literal => {
+                    self.skip_whitespace();
+                    self.advance();
+                    AddressTargetKind::Environment {
+                        name: token,
+                        command: self.parse_optional_expression(&[])?,
+                    }
+                }
+                _ => AddressTargetKind::Value(self.parse_expression(&[])?),
+            }
+        };
+        let span = self.span_from(start);
+        self.expect_clause_end()?;
+        Ok(AddressTarget { kind, span })
+    }
+
+    /// Parses the names after DROP or PROCEDURE EXPOSE up to the end of the
+    /// clause. The list must not be empty.
+    pub(super) fn parse_variable_list(
+        &mut self,
+        keyword: &Token,
+    ) -> ParseResult<Vec<VariableReference>> {
+        let mut variables = Vec::new();
+        while variables.is_empty() || !self.at_clause_end() {
+            self.skip_whitespace();
+            let start = self.position;
+            let token = self.advance();
+            let kind = match token.token_type {
+                TokenType::LeftParen => {
+                    let name = self.peek_significant().clone();
+                    if !is_variable(&name) {
+                        return Err(ParseError::ExpectedSymbol {
+                            keyword: Box::new(keyword.clone()),
+                            found: name,
+                        });
+                    }
+                    self.skip_whitespace();
+                    self.advance();
+                    let close = self.peek_significant().clone();
+                    if close.token_type != TokenType::RightParen {
+                        return Err(ParseError::InvalidVariableReference(close));
+                    }
+                    self.skip_whitespace();
+                    self.advance();
+                    VariableReferenceKind::Indirect(name)
+                }
+                _ if is_variable(&token) => VariableReferenceKind::Variable(token),
+                _ => {
+                    return Err(ParseError::ExpectedSymbol {
+                        keyword: Box::new(keyword.clone()),
+                        found: token,
+                    })
+                }
+            };
+            variables.push(VariableReference {
+                kind,
+                span: self.span_from(start),
+            });
+        }
+        self.expect_clause_end()?;
+        Ok(variables)
+    }
+
+    /// Parses the optional control variable after ITERATE or LEAVE, which
+    /// is always a simple symbol.
+    pub(super) fn parse_loop_name(&mut self, keyword: Token) -> ParseResult<Option<Token>> {
+        if self.at_clause_end() {
+            self.expect_clause_end()?;
+            return Ok(None);
+        }
+        let name = self.peek_significant().clone();
+        if name.token_type != TokenType::Symbol(SymbolKind::Simple) {
+            return Err(ParseError::ExpectedSymbol {
+                keyword: Box::new(keyword),
+                found: name,
+            });
+        }
+        self.skip_whitespace();
+        self.advance();
+        self.expect_clause_end()?;
+        Ok(Some(name))
+    }
+
+    /// Parses a NUMERIC instruction after its keyword.
+    pub(super) fn parse_numeric(&mut self) -> ParseResult<NumericSetting> {
+        let start = self.position;
+        let token = self.peek_significant().clone();
+        let keyword = self.keyword(&token);
+        if !matches!(keyword.as_str(), "DIGITS" | "FUZZ" | "FORM") {
+            return Err(ParseError::InvalidNumericKeyword(token));
+        }
+        self.skip_whitespace();
+        self.advance();
+        let kind = match keyword.as_str() {
+            "DIGITS" => NumericSettingKind::Digits(self.parse_optional_expression(&[])?),
+            "FUZZ" => NumericSettingKind::Fuzz(self.parse_optional_expression(&[])?),
+            _ => {
+                let start = self.position;
+                NumericSettingKind::Form(self.parse_numeric_form()?.map(|kind| NumericForm {
+                    kind,
+                    span: self.span_from(start),
+                }))
+            }
+        };
+        let span = self.span_from(start);
+        self.expect_clause_end()?;
+        Ok(NumericSetting { kind, span })
+    }
+
+    /// The setting after NUMERIC FORM, if any.
+    fn parse_numeric_form(&mut self) -> ParseResult<Option<NumericFormKind>> {
+        let token = self.peek_significant().clone();
+        let form = match self.keyword(&token).as_str() {
+            "SCIENTIFIC" => NumericFormKind::Scientific,
+            "ENGINEERING" => NumericFormKind::Engineering,
+            "VALUE" => {
+                self.skip_whitespace();
+                self.advance();
+                return Ok(Some(NumericFormKind::Value(self.parse_expression(&[])?)));
+            }
+            _ if self.at_clause_end() => return Ok(None),
+            _ => match token.token_type {
+                TokenType::Symbol(_) | TokenType::Literal => {
+                    return Err(ParseError::InvalidNumericForm(token))
+                }
+                _ => return Ok(Some(NumericFormKind::Value(self.parse_expression(&[])?))),
+            },
+        };
+        self.skip_whitespace();
+        self.advance();
+        Ok(Some(form))
+    }
+
+    /// Parses a PROCEDURE instruction after its keyword.
+    pub(super) fn parse_procedure(&mut self) -> ParseResult<InstructionKind> {
+        let token = self.peek_significant().clone();
+        if self.keyword(&token) == "EXPOSE" {
+            self.skip_whitespace();
+            self.advance();
+            return Ok(InstructionKind::Procedure {
+                expose: self.parse_variable_list(&token)?,
+            });
+        }
+        if !self.at_clause_end() {
+            return Err(ParseError::InvalidProcedureKeyword(token));
+        }
+        self.expect_clause_end()?;
+        Ok(InstructionKind::Procedure { expose: Vec::new() })
+    }
+
+    /// Parses a TRACE instruction after its keyword. As with ADDRESS, VALUE
+    /// may only be left out when the expression does not start with a
+    /// symbol or a string.
+    pub(super) fn parse_trace(&mut self) -> ParseResult<TraceSetting> {
+        let start = self.position;
+        let token = self.peek_significant().clone();
+        let kind = if self.keyword(&token) == "VALUE" {
+            self.skip_whitespace();
+            self.advance();
+            TraceSettingKind::Value(self.parse_expression(&[])?)
+        } else {
+            match token.token_type {
+                _ if self.at_clause_end() => TraceSettingKind::Default,
+                TokenType::Symbol(SymbolKind::Number) => {
+                    self.skip_whitespace();
+                    self.advance();
+                    TraceSettingKind::Setting(token)
+                }
+                TokenType::Symbol(_) | TokenType::Literal => {
+                    // `?` alone only toggles interactive tracing, and an
+                    // empty string restores the default setting.
+                    let text = self.get_text(&token).trim_matches(['\'', '"']);
+                    let letter = text.trim_start_matches('?').chars().next();
+                    let valid = match letter {
+                        Some(letter) => TRACE_LETTERS.contains(letter.to_ascii_uppercase()),
+                        None => text.is_empty() || text.starts_with('?'),
+                    };
+                    if !valid {
+                        return Err(ParseError::InvalidTraceSetting(token));
+                    }
+                    self.skip_whitespace();
+                    self.advance();
+                    TraceSettingKind::Setting(token)
+                }
+                _ => TraceSettingKind::Value(self.parse_expression(&[])?),
+            }
+        };
+        let span = self.span_from(start);
+        self.expect_clause_end()?;
+        Ok(TraceSetting { kind, span })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{ExpressionKind, Program};
+    use crate::parser::kinds;
+
+    fn parse(source: &str) -> ParseResult<Program> {
+        let mut parser = RexxParser::new(source);
+        parser.parse()
+    }
+
+    #[test]
+    fn parse_address() {
+        let program =
+            parse("address\naddress system\naddress 'CMD' 'dir' x\naddress value env").unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [
+                InstructionKind::Address(AddressTarget {
+                    kind: AddressTargetKind::Swap,
+                    ..
+                }),
+                InstructionKind::Address(AddressTarget {
+                    kind: AddressTargetKind::Environment { command: None, .. },
+                    ..
+                }),
+                InstructionKind::Address(AddressTarget {
+                    kind: AddressTargetKind::Environment {
+                        command: Some(Expression {
+                            kind: ExpressionKind::Binary { .. },
+                            ..
+                        }),
+                        ..
+                    },
+                    ..
+                }),
+                InstructionKind::Address(AddressTarget {
+                    kind: AddressTargetKind::Value(Expression {
+                        kind: ExpressionKind::Symbol(_),
+                        ..
+                    }),
+                    ..
+                }),
+            ]
+        ));
+    }
+
+    #[test]
+    fn parse_variable_lists() {
+        let program = parse("drop a b. c.i (list)\nprocedure expose x (y)").unwrap();
+        let instructions = kinds(&program.instructions);
+        let [InstructionKind::Drop(dropped), InstructionKind::Procedure { expose }] =
+            &instructions[..]
+        else {
+            panic!("unexpected instructions: {:?}", program.instructions);
+        };
+        assert!(matches!(
+            dropped[..],
+            [
+                VariableReference {
+                    kind: VariableReferenceKind::Variable(_),
+                    ..
+                },
+                VariableReference {
+                    kind: VariableReferenceKind::Variable(_),
+                    ..
+                },
+                VariableReference {
+                    kind: VariableReferenceKind::Variable(_),
+                    ..
+                },
+                VariableReference {
+                    kind: VariableReferenceKind::Indirect(_),
+                    ..
+                },
+            ]
+        ));
+        assert!(matches!(
+            expose[..],
+            [
+                VariableReference {
+                    kind: VariableReferenceKind::Variable(_),
+                    ..
+                },
+                VariableReference {
+                    kind: VariableReferenceKind::Indirect(_),
+                    ..
+                }
+            ]
+        ));
+    }
+
+    #[test]
+    fn parse_simple_keyword_instructions() {
+        let program = parse(
+            "exit\nexit 1\nreturn x + 1\ninterpret 'say' x\noptions 'ETMODE'\n\
+             push\nqueue line\niterate\nleave i\nprocedure",
+        )
+        .unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [
+                InstructionKind::Exit(None),
+                InstructionKind::Exit(Some(_)),
+                InstructionKind::Return(Some(Expression {
+                    kind: ExpressionKind::Binary { .. },
+                    ..
+                })),
+                InstructionKind::Interpret(_),
+                InstructionKind::Options(_),
+                InstructionKind::Push(None),
+                InstructionKind::Queue(Some(_)),
+                InstructionKind::Iterate(None),
+                InstructionKind::Leave(Some(_)),
+                InstructionKind::Procedure { expose },
+            ] if expose.is_empty()
+        ));
+    }
+
+    #[test]
+    fn parse_numeric_and_trace() {
+        let program = parse(
+            "numeric digits 12\nnumeric fuzz\nnumeric form\nnumeric form engineering\n\
+             numeric form value f()\ntrace\ntrace ?R\ntrace 'Off'\ntrace -5\ntrace value t\n\
+             trace ?\ntrace '?'\ntrace ''",
+        )
+        .unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [
+                InstructionKind::Numeric(NumericSetting {
+                    kind: NumericSettingKind::Digits(Some(_)),
+                    ..
+                }),
+                InstructionKind::Numeric(NumericSetting {
+                    kind: NumericSettingKind::Fuzz(None),
+                    ..
+                }),
+                InstructionKind::Numeric(NumericSetting {
+                    kind: NumericSettingKind::Form(None),
+                    ..
+                }),
+                InstructionKind::Numeric(NumericSetting {
+                    kind: NumericSettingKind::Form(Some(NumericForm {
+                        kind: NumericFormKind::Engineering,
+                        ..
+                    })),
+                    ..
+                }),
+                InstructionKind::Numeric(NumericSetting {
+                    kind: NumericSettingKind::Form(Some(NumericForm {
+                        kind: NumericFormKind::Value(_),
+                        ..
+                    })),
+                    ..
+                }),
+                InstructionKind::Trace(TraceSetting {
+                    kind: TraceSettingKind::Default,
+                    ..
+                }),
+                InstructionKind::Trace(TraceSetting {
+                    kind: TraceSettingKind::Setting(_),
+                    ..
+                }),
+                InstructionKind::Trace(TraceSetting {
+                    kind: TraceSettingKind::Setting(_),
+                    ..
+                }),
+                InstructionKind::Trace(TraceSetting {
+                    kind: TraceSettingKind::Value(Expression {
+                        kind: ExpressionKind::Prefix { .. },
+                        ..
+                    }),
+                    ..
+                }),
+                InstructionKind::Trace(TraceSetting {
+                    kind: TraceSettingKind::Value(Expression {
+                        kind: ExpressionKind::Symbol(_),
+                        ..
+                    }),
+                    ..
+                }),
+                InstructionKind::Trace(TraceSetting {
+                    kind: TraceSettingKind::Setting(_),
+                    ..
+                }),
+                InstructionKind::Trace(TraceSetting {
+                    kind: TraceSettingKind::Setting(_),
+                    ..
+                }),
+                InstructionKind::Trace(TraceSetting {
+                    kind: TraceSettingKind::Setting(_),
+                    ..
+                }),
+            ]
+        ));
+    }
+
+    #[test]
+    fn parse_keyword_instruction_errors() {
+        assert!(matches!(
+            parse("drop"),
+            Err(ParseError::ExpectedSymbol { .. })
+        ));
+        assert!(matches!(
+            parse("drop a 'b'"),
+            Err(ParseError::ExpectedSymbol { .. })
+        ));
+        assert!(matches!(
+            parse("drop (a b)"),
+            Err(ParseError::InvalidVariableReference(_))
+        ));
+        assert!(matches!(
+            parse("procedure hide x"),
+            Err(ParseError::InvalidProcedureKeyword(_))
+        ));
+        assert!(matches!(
+            parse("numeric width 5"),
+            Err(ParseError::InvalidNumericKeyword(_))
+        ));
+        assert!(matches!(
+            parse("numeric form exponential"),
+            Err(ParseError::InvalidNumericForm(_))
+        ));
+        assert!(matches!(
+            parse("trace x"),
+            Err(ParseError::InvalidTraceSetting(_))
+        ));
+        assert!(matches!(
+            parse("leave 1"),
+            Err(ParseError::ExpectedSymbol { .. })
+        ));
+        assert!(matches!(
+            parse("leave a.b"),
+            Err(ParseError::ExpectedSymbol { .. })
+        ));
+        assert!(matches!(
+            parse("iterate list."),
+            Err(ParseError::ExpectedSymbol { .. })
+        ));
+        assert!(matches!(
+            parse("trace '?x'"),
+            Err(ParseError::InvalidTraceSetting(_))
+        ));
+        assert!(matches!(
+            parse("interpret"),
+            Err(ParseError::InvalidExpression(_))
+        ));
+        assert!(matches!(
+            parse("exit 1, 2"),
+            Err(ParseError::UnexpectedComma(_))
+        ));
+        assert!(matches!(
+            parse("nop x"),
+            Err(ParseError::InvalidExpression(_))
+        ));
+    }
+}
@@ -0,0 +1,22 @@
+mod clause;
+mod control;
+mod expression;
+mod keyword;
+#[allow(clippy::module_inception)]
+pub mod parser;
+mod template;
+mod transfer;
+
+pub(crate) use clause::{clause_start, clause_tokens, keyword, ClauseStart};
+pub(crate) use expression::{binary_operator, CONCATENATION_PRIORITY};
+pub use parser::*;
+
+/// The kinds of the instructions, to match them with slice patterns in
+/// tests.
+#[cfg(test)]
+fn kinds(instructions: &[crate::ast::Instruction]) -> Vec<&crate::ast::InstructionKind> {
+    instructions
+        .iter()
+        .map(|instruction| &instruction.kind)
+        .collect()
+}
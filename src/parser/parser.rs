@@ -0,0 +1,708 @@
+use crate::ast::{Expression, Instruction, InstructionKind, Program, Span};
+use crate::diagnostics::Diagnostic;
+use crate::lexer::{LexError, Lexer, SymbolKind, Token, TokenType};
+
+use super::clause::{self, clause_start, clause_tokens, ClauseStart};
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// Msg35.1: Invalid expression detected at "<token>".
+    InvalidExpression(Token),
+    /// Msg36: Unmatched "(" in expression.
+    UnmatchedParenthesis(Token),
+    /// Msg37.1: Unexpected ",".
+    UnexpectedComma(Token),
+    /// Msg37.2: Unmatched ")" in expression.
+    UnexpectedParenthesis(Token),
+    /// Msg31.1, Msg31.2 or Msg31.3: a value assigned to a constant symbol.
+    InvalidAssignmentTarget(Token),
+    /// Msg10.1: END has no corresponding DO or SELECT.
+    UnmatchedEnd(Token),
+    /// Msg10.2: END corresponding to DO on line <line> must have a symbol
+    /// following that matches the control variable (or no symbol); found
+    /// "<symbol>". The first token is the DO keyword.
+    EndNameMismatch { keyword: Box<Token>, found: Token },
+    /// Msg10.3: END corresponding to DO on line <line> must not have a
+    /// symbol following it because there is no control variable; found
+    /// "<symbol>".
+    UnexpectedEndName { keyword: Box<Token>, found: Token },
+    /// Msg14.1: DO instruction requires a matching END.
+    IncompleteDo(Token),
+    /// Msg27.1: Invalid use of keyword "<keyword>" in DO clause.
+    InvalidDoKeyword(Token),
+    /// Msg8.1: THEN has no corresponding IF or WHEN clause.
+    UnexpectedThen(Token),
+    /// Msg8.2: ELSE has no corresponding THEN clause.
+    UnexpectedElse(Token),
+    /// Msg9.1: WHEN has no corresponding SELECT.
+    UnexpectedWhen(Token),
+    /// Msg9.2: OTHERWISE has no corresponding SELECT.
+    UnexpectedOtherwise(Token),
+    /// Msg18.1 (after IF) or Msg18.2 (after WHEN): <keyword> on line <line>
+    /// requires matching THEN clause; found "<token>".
+    MissingThen { keyword: Box<Token>, found: Token },
+    /// Msg14.2: SELECT instruction requires a matching END.
+    IncompleteSelect(Token),
+    /// Msg14.3: THEN requires a following instruction.
+    IncompleteThen(Token),
+    /// Msg14.4: ELSE requires a following instruction.
+    IncompleteElse(Token),
+    /// Msg10.5: END must not immediately follow THEN.
+    EndAfterThen(Token),
+    /// Msg10.6: END must not immediately follow ELSE.
+    EndAfterElse(Token),
+    /// Msg7.1: SELECT on line <line> requires WHEN; found "<token>".
+    MissingWhen { keyword: Box<Token>, found: Token },
+    /// Msg7.2: SELECT on line <line> requires WHEN, OTHERWISE, or END;
+    /// found "<token>".
+    InvalidSelectClause { keyword: Box<Token>, found: Token },
+    /// Msg10.4: END corresponding to SELECT on line <line> must not have a
+    /// symbol following; found "<symbol>".
+    SelectEndName { keyword: Box<Token>, found: Token },
+    /// Msg25.12: PARSE must be followed by one of the keywords ARG, LINEIN,
+    /// PULL, SOURCE, VALUE, VAR, or VERSION; found "<token>".
+    InvalidParseKeyword(Token),
+    /// Msg20.1: Symbol expected after <keyword>; found "<token>".
+    ExpectedSymbol { keyword: Box<Token>, found: Token },
+    /// Msg38.1: Invalid parsing template detected at "<token>".
+    InvalidTemplate(Token),
+    /// Msg38.2: Invalid parsing position detected at "<token>".
+    InvalidPosition(Token),
+    /// Msg38.3: PARSE VALUE instruction requires WITH keyword.
+    MissingWith(Token),
+    /// Msg21.1: The clause ended at an unexpected token; found "<token>".
+    UnexpectedToken(Token),
+    /// Msg19.2: String or symbol expected after CALL; found "<token>".
+    ExpectedRoutineName(Token),
+    /// Msg19.3: String or symbol expected after NAME; found "<token>".
+    ExpectedTrapName(Token),
+    /// Msg25.1 (CALL ON) or Msg25.2 (CALL OFF): <keyword> must be followed
+    /// by one of the keywords ERROR, FAILURE, HALT, or NOTREADY; found
+    /// "<token>". The first token is the ON or OFF keyword.
+    InvalidCallCondition { keyword: Box<Token>, found: Token },
+    /// Msg19.4: String or symbol expected after SIGNAL; found "<token>".
+    ExpectedLabelName(Token),
+    /// Msg25.3 (SIGNAL ON) or Msg25.4 (SIGNAL OFF): <keyword> must be
+    /// followed by one of the keywords ERROR, FAILURE, HALT, LOSTDIGITS,
+    /// NOTREADY, NOVALUE, or SYNTAX; found "<token>". The first token is the
+    /// ON or OFF keyword.
+    InvalidSignalCondition { keyword: Box<Token>, found: Token },
+    /// Msg46.1: Extra token "<token>" found in variable reference; ")"
+    /// expected.
+    InvalidVariableReference(Token),
+    /// Msg25.15: NUMERIC must be followed by one of the keywords DIGITS,
+    /// FORM, or FUZZ; found "<token>".
+    InvalidNumericKeyword(Token),
+    /// Msg25.11: NUMERIC FORM must be followed by one of the keywords
+    /// ENGINEERING or SCIENTIFIC; found "<token>".
+    InvalidNumericForm(Token),
+    /// Msg25.17: PROCEDURE must be followed by the keyword EXPOSE or
+    /// nothing; found "<token>".
+    InvalidProcedureKeyword(Token),
+    /// Msg24.1: TRACE request letter must be one of "ACEFILNOR"; found
+    /// "<value>".
+    InvalidTraceSetting(Token),
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+pub struct RexxParser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pub(super) position: usize,
+    /// Keywords that end the expression being parsed, such as `THEN`.
+    pub(super) terminators: &'static [&'static str],
+    /// The errors found so far, in source order.
+    pub(super) errors: Vec<ParseError>,
+    /// The errors of the lexer in the last parse.
+    lex_errors: Vec<LexError>,
+}
+
+impl<'a> RexxParser<'a> {
+    pub fn new(source: &'a str) -> RexxParser<'a> {
+        RexxParser {
+            source,
+            tokens: Vec::new(),
+            position: 0,
+            terminators: &[],
+            errors: Vec::new(),
+            lex_errors: Vec::new(),
+        }
+    }
+
+    /// Parses the whole source and fails with the first error.
+    pub fn parse(&mut self) -> ParseResult<Program> {
+        let program = self.parse_program();
+        match self.errors.drain(..).next() {
+            Some(error) => Err(error),
+            None => Ok(program),
+        }
+    }
+
+    /// Parses the whole source, going on after each error at the next
+    /// clause. Returns the instructions that could be parsed together with
+    /// the diagnostics of both the lexer and the parser, in source order.
+    pub fn parse_recovering(&mut self) -> (Program, Vec<Diagnostic>) {
+        let program = self.parse_program();
+        let source = self.source;
+        let mut diagnostics = self
+            .lex_errors
+            .iter()
+            .map(Diagnostic::from_lex_error)
+            .chain(
+                self.errors
+                    .drain(..)
+                    .map(|error| Diagnostic::from_parse_error(&error, source)),
+            )
+            .collect::<Vec<_>>();
+        diagnostics.sort_by_key(|diagnostic| diagnostic.range.start.index);
+        (program, diagnostics)
+    }
+
+    fn parse_program(&mut self) -> Program {
+        let mut instructions = vec![];
+        let mut lexer = Lexer::new(self.source);
+        self.tokens = clause_tokens(lexer.tokenize());
+        // Comments only matter to the syntax tree.
+        self.tokens
+            .retain(|token| token.token_type != TokenType::Comment);
+        self.lex_errors = lexer.diagnostics().to_vec();
+        self.position = 0;
+        self.errors.clear();
+        while self.peek_significant().token_type != TokenType::EOS {
+            if let Some(instruction) = self.parse_clause() {
+                instructions.push(instruction);
+            }
+        }
+        Program {
+            instructions,
+            span: self.span_from(0),
+        }
+    }
+
+    /// Like `parse_instruction`, but an error is recorded and parsing goes
+    /// on with the next clause.
+    pub(super) fn parse_clause(&mut self) -> Option<Instruction> {
+        let start = self.position;
+        match self.parse_instruction() {
+            Ok(instruction) => instruction,
+            Err(error) => {
+                self.recover(error, start);
+                None
+            }
+        }
+    }
+
+    /// Records an error in the construct that started at `start` and skips
+    /// to the start of the next clause. Nothing is skipped when the error
+    /// was found at the start of a clause, such as an END after THEN, as
+    /// that clause may still close a group.
+    pub(super) fn recover(&mut self, error: ParseError, start: usize) {
+        self.errors.push(error);
+        let consumed = self.tokens[start..self.position]
+            .iter()
+            .any(|token| token.token_type != TokenType::Whitespace);
+        if !consumed || !self.at_clause_start() {
+            self.skip_clause();
+        }
+    }
+
+    /// Consumes the end of the current clause, or records an error and skips
+    /// to the next clause.
+    pub(super) fn recover_clause_end(&mut self) {
+        let start = self.position;
+        if let Err(error) = self.expect_clause_end() {
+            self.recover(error, start);
+        }
+    }
+
+    /// Whether the next token starts a clause: it follows a clause
+    /// terminator, or a THEN, ELSE or OTHERWISE, which end a clause by
+    /// themselves.
+    fn at_clause_start(&self) -> bool {
+        let previous = self.tokens[..self.position]
+            .iter()
+            .rev()
+            .find(|token| token.token_type != TokenType::Whitespace);
+        match previous {
+            None => true,
+            Some(token) => {
+                matches!(token.token_type, TokenType::EOL | TokenType::Semicolon)
+                    || matches!(self.keyword(token).as_str(), "THEN" | "ELSE" | "OTHERWISE")
+            }
+        }
+    }
+
+    /// Parses the next clause. Null clauses give no instruction.
+    pub(super) fn parse_instruction(&mut self) -> ParseResult<Option<Instruction>> {
+        self.skip_whitespace();
+        let start = self.position;
+        let token = self.peek().clone();
+        let kind = match token.token_type {
+            TokenType::EOL | TokenType::Semicolon => {
+                // A null clause
+                self.advance();
+                return Ok(None);
+            }
+            TokenType::Unknown => {
+                self.skip_clause();
+                InstructionKind::Unknown(token)
+            }
+            _ => match self.clause_start() {
+                ClauseStart::Label => {
+                    self.advance();
+                    self.skip_whitespace();
+                    self.advance();
+                    InstructionKind::Label(token)
+                }
+                ClauseStart::Assignment => self.parse_assignment()?,
+                ClauseStart::Keyword("END") => return Err(ParseError::UnmatchedEnd(token)),
+                ClauseStart::Keyword(_) => self.parse_kayword_instruction()?,
+                _ => self.parse_command()?,
+            },
+        };
+        Ok(Some(Instruction {
+            kind,
+            span: self.span_from(start),
+        }))
+    }
+
+    /// Any other clause is an expression whose value is a command for the
+    /// environment.
+    fn parse_command(&mut self) -> ParseResult<InstructionKind> {
+        let expression = self.parse_expression(&[])?;
+        self.expect_clause_end()?;
+        Ok(InstructionKind::Command(expression))
+    }
+
+    /// What the clause at the current position is.
+    fn clause_start(&self) -> ClauseStart {
+        let second = self.peek_significant_nth(1);
+        clause_start(self.source, self.peek_significant(), Some(second))
+    }
+    pub(super) fn is_assignment(&self) -> bool {
+        self.clause_start() == ClauseStart::Assignment
+    }
+    /// `symbol = expression`. Any symbol followed by `=` starts an
+    /// assignment, even when it is a keyword such as `say`.
+    fn parse_assignment(&mut self) -> ParseResult<InstructionKind> {
+        let (target, expression) = self.parse_assignment_parts(&[])?;
+        self.expect_clause_end()?;
+        Ok(InstructionKind::Assignment { target, expression })
+    }
+    /// The target and the expression of an assignment, which DO also uses
+    /// for its control variable.
+    pub(super) fn parse_assignment_parts(
+        &mut self,
+        terminators: &'static [&'static str],
+    ) -> ParseResult<(Token, Expression)> {
+        self.skip_whitespace();
+        let target = self.advance();
+        if matches!(
+            target.token_type,
+            TokenType::Symbol(SymbolKind::Number | SymbolKind::Constant)
+        ) {
+            return Err(ParseError::InvalidAssignmentTarget(target));
+        }
+        self.skip_whitespace();
+        self.advance(); // `=`
+        let expression = self.parse_expression(terminators)?;
+        Ok((target, expression))
+    }
+    fn parse_kayword_instruction(&mut self) -> ParseResult<InstructionKind> {
+        let token = self.advance();
+        let kind = match self.keyword(&token).as_str() {
+            "DO" => self.parse_do(token)?,
+            "IF" => self.parse_if(token)?,
+            "SELECT" => self.parse_select(token)?,
+            "ADDRESS" => InstructionKind::Address(self.parse_address()?),
+            "ARG" => InstructionKind::Arg(self.parse_template_list()?),
+            "CALL" => self.parse_call()?,
+            "DROP" => InstructionKind::Drop(self.parse_variable_list(&token)?),
+            "EXIT" => InstructionKind::Exit(self.parse_optional_operand()?),
+            "INTERPRET" => InstructionKind::Interpret(self.parse_operand()?),
+            "ITERATE" => InstructionKind::Iterate(self.parse_loop_name(token)?),
+            "LEAVE" => InstructionKind::Leave(self.parse_loop_name(token)?),
+            "NOP" => {
+                self.expect_clause_end()?;
+                InstructionKind::Nop
+            }
+            "NUMERIC" => InstructionKind::Numeric(self.parse_numeric()?),
+            "OPTIONS" => InstructionKind::Options(self.parse_operand()?),
+            "PARSE" => self.parse_parse()?,
+            "PROCEDURE" => self.parse_procedure()?,
+            "PULL" => InstructionKind::Pull(self.parse_template_list()?),
+            "PUSH" => InstructionKind::Push(self.parse_optional_operand()?),
+            "QUEUE" => InstructionKind::Queue(self.parse_optional_operand()?),
+            "RETURN" => InstructionKind::Return(self.parse_optional_operand()?),
+            "SAY" => InstructionKind::Say(self.parse_optional_operand()?),
+            "SIGNAL" => self.parse_signal()?,
+            "TRACE" => InstructionKind::Trace(self.parse_trace()?),
+            "THEN" => return Err(ParseError::UnexpectedThen(token)),
+            "ELSE" => return Err(ParseError::UnexpectedElse(token)),
+            "WHEN" => return Err(ParseError::UnexpectedWhen(token)),
+            "OTHERWISE" => return Err(ParseError::UnexpectedOtherwise(token)),
+            _ => {
+                self.skip_clause();
+                InstructionKind::Unknown(token)
+            }
+        };
+        Ok(kind)
+    }
+
+    pub fn get_text(&self, token: &Token) -> &'a str {
+        &self.source[token.range.start.index..token.range.end.index]
+    }
+
+    /// The uppercase text of a simple symbol, used to match keywords. Any
+    /// other token gives an empty string.
+    pub(super) fn keyword(&self, token: &Token) -> String {
+        clause::keyword(self.source, token)
+    }
+
+    pub(super) fn peek(&self) -> &Token {
+        &self.tokens[self.position.min(self.tokens.len() - 1)]
+    }
+
+    /// The `n`th token from the current position, not counting blanks.
+    pub(super) fn peek_significant_nth(&self, n: usize) -> &Token {
+        let mut remaining = n;
+        for token in &self.tokens[self.position.min(self.tokens.len() - 1)..] {
+            if token.token_type == TokenType::Whitespace {
+                continue;
+            }
+            if remaining == 0 || token.token_type == TokenType::EOS {
+                return token;
+            }
+            remaining -= 1;
+        }
+        self.tokens.last().unwrap()
+    }
+
+    pub(super) fn peek_significant(&self) -> &Token {
+        self.peek_significant_nth(0)
+    }
+
+    pub(super) fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        if self.position < self.tokens.len() - 1 {
+            self.position += 1;
+        }
+        token
+    }
+
+    /// The span of the tokens consumed since the position `start`, leaving
+    /// out blanks and clause terminators. When there are none, it is empty
+    /// and at the next token.
+    pub(super) fn span_from(&self, start: usize) -> Span {
+        let mut tokens = self.tokens[start..self.position].iter().filter(|token| {
+            !matches!(
+                token.token_type,
+                TokenType::Whitespace | TokenType::EOL | TokenType::Semicolon | TokenType::EOS
+            )
+        });
+        let Some(first) = tokens.next() else {
+            let start = self.peek_significant().range.start.clone();
+            return Span {
+                start: start.clone(),
+                end: start,
+            };
+        };
+        let last = tokens.next_back().unwrap_or(first);
+        Span {
+            start: first.range.start.clone(),
+            end: last.range.end.clone(),
+        }
+    }
+
+    /// Whether the current clause starts with the keyword. A label or an
+    /// assignment that uses the keyword as its name does not count.
+    pub(super) fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.clause_start(), ClauseStart::Keyword(found) if found == keyword)
+    }
+
+    /// Skips null clauses: blanks and clause terminators other than `EOS`.
+    pub(super) fn skip_null_clauses(&mut self) {
+        while matches!(
+            self.peek_significant().token_type,
+            TokenType::EOL | TokenType::Semicolon
+        ) {
+            self.skip_whitespace();
+            self.advance();
+        }
+    }
+
+    /// Skips blanks and returns whether there were any.
+    pub(super) fn skip_whitespace(&mut self) -> bool {
+        let mut skipped = false;
+        while self.peek().token_type == TokenType::Whitespace {
+            self.advance();
+            skipped = true;
+        }
+        skipped
+    }
+
+    pub(super) fn at_clause_end(&self) -> bool {
+        matches!(
+            self.peek_significant().token_type,
+            TokenType::EOL | TokenType::Semicolon | TokenType::EOS
+        )
+    }
+
+    /// Consumes the end of the current clause, or reports the first token
+    /// that should not be there.
+    pub(super) fn expect_clause_end(&mut self) -> ParseResult<()> {
+        self.expect_clause_end_or(ParseError::InvalidExpression)
+    }
+
+    /// Like `expect_clause_end`, but reports any other token with
+    /// `unexpected`. CALL and SIGNAL report the tokens after their operands
+    /// as Msg21.1 rather than as an invalid expression.
+    pub(super) fn expect_clause_end_or(
+        &mut self,
+        unexpected: fn(Token) -> ParseError,
+    ) -> ParseResult<()> {
+        if self.at_clause_end() {
+            self.skip_clause();
+            return Ok(());
+        }
+        let token = self.peek_significant().clone();
+        Err(match token.token_type {
+            TokenType::Comma => ParseError::UnexpectedComma(token),
+            TokenType::RightParen => ParseError::UnexpectedParenthesis(token),
+            _ => unexpected(token),
+        })
+    }
+
+    /// Skips the rest of the current clause, including its terminator.
+    pub(super) fn skip_clause(&mut self) {
+        loop {
+            match self.advance().token_type {
+                TokenType::EOL | TokenType::Semicolon | TokenType::EOS => break,
+                _ => {}
+            }
+        }
+    }
+}
+
+/*
+keyword_instruction :=
+    address |
+    arg |
+    call |
+    drop |
+    exit |
+    interpret |
+    iterate |
+    leave |
+    nop |
+    numeric |
+    options |
+    parse |
+    procedure |
+    pull |
+    push |
+    queue |
+    return |
+    say |
+    signal |
+    trace |
+    'THEN' Msg8.1 |
+    'ELSE' Msg8.2 |
+    'WHEN' Msg9.1 |
+    'OTHERWISE' Msg9.2
+*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{NumericSetting, NumericSettingKind};
+    use crate::parser::kinds;
+
+    fn parse(source: &str) -> ParseResult<Program> {
+        let mut parser = RexxParser::new(source);
+        parser.parse()
+    }
+
+    #[test]
+    fn parse_assignments() {
+        let source = "x = 5\nstem. = ''; a.i.j = x + 1\nsay = 1";
+        let program = parse(source).unwrap();
+        let targets = program
+            .instructions
+            .iter()
+            .map(|instruction| match &instruction.kind {
+                InstructionKind::Assignment { target, .. } => {
+                    &source[target.range.start.index..target.range.end.index]
+                }
+                other => panic!("not an assignment: {other:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(targets, vec!["x", "stem.", "a.i.j", "say"]);
+    }
+
+    #[test]
+    fn parse_invalid_assignments() {
+        assert!(matches!(
+            parse("3 = 4"),
+            Err(ParseError::InvalidAssignmentTarget(_))
+        ));
+        assert!(matches!(
+            parse(".x = 4"),
+            Err(ParseError::InvalidAssignmentTarget(_))
+        ));
+        assert!(matches!(
+            parse("x ="),
+            Err(ParseError::InvalidExpression(_))
+        ));
+    }
+
+    #[test]
+    fn parse_spans() {
+        let source = "start: say 'a' b /* c */ ;\nif x then\n  call f 1,,\n    (2); else nop\n\
+                      parse arg a ., +1 b\nsay";
+        let program = parse(source).unwrap();
+        let text = |span: &Span| &source[span.start.index..span.end.index];
+        let texts = program
+            .instructions
+            .iter()
+            .map(|instruction| text(&instruction.span))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                "start:",
+                "say 'a' b",
+                "if x then\n  call f 1,,\n    (2); else nop",
+                "parse arg a ., +1 b",
+                "say",
+            ]
+        );
+        assert_eq!(text(&program.span), source);
+
+        let InstructionKind::Say(Some(expression)) = &program.instructions[1].kind else {
+            panic!("unexpected instruction: {:?}", program.instructions[1]);
+        };
+        assert_eq!(text(&expression.span), "'a' b");
+        let InstructionKind::If { then_branch, .. } = &program.instructions[2].kind else {
+            panic!("unexpected instruction: {:?}", program.instructions[2]);
+        };
+        assert_eq!(text(&then_branch.span), "call f 1,,\n    (2)");
+        let InstructionKind::Parse { templates, .. } = &program.instructions[3].kind else {
+            panic!("unexpected instruction: {:?}", program.instructions[3]);
+        };
+        let templates = templates
+            .iter()
+            .map(|template| {
+                let elements = template.elements.iter().map(|element| text(&element.span));
+                (text(&template.span), elements.collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            templates,
+            vec![("a .", vec!["a", "."]), ("+1 b", vec!["+1", "b"])]
+        );
+    }
+
+    #[test]
+    fn parse_operand_spans() {
+        let source = "call f 1\ncall on error name trap\nsignal value 'L' ;\naddress\n\
+                      address cmd 'ls'\ndrop a ( b )\nnumeric form value f\ntrace ?r\n\
+                      parse upper value x with y\ndo i = 1 to 3 while i < 2; end";
+        let program = parse(source).unwrap();
+        let text = |span: &Span| &source[span.start.index..span.end.index];
+        let texts = program
+            .instructions
+            .iter()
+            .flat_map(|instruction| match &instruction.kind {
+                InstructionKind::Call { target, .. } => vec![text(&target.span)],
+                InstructionKind::Signal(target) => vec![text(&target.span)],
+                InstructionKind::Address(target) => vec![text(&target.span)],
+                InstructionKind::Drop(variables) => variables
+                    .iter()
+                    .map(|variable| text(&variable.span))
+                    .collect(),
+                InstructionKind::Numeric(NumericSetting {
+                    kind: NumericSettingKind::Form(Some(form)),
+                    span,
+                }) => vec![text(span), text(&form.span)],
+                InstructionKind::Trace(setting) => vec![text(&setting.span)],
+                InstructionKind::Parse { source, .. } => vec![text(&source.span)],
+                InstructionKind::Do {
+                    repetitor: Some(repetitor),
+                    conditional: Some(conditional),
+                    ..
+                } => vec![text(&repetitor.span), text(&conditional.span)],
+                _ => panic!("unexpected instruction: {instruction:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec![
+                "f",
+                "on error name trap",
+                "value 'L'",
+                "",
+                "cmd 'ls'",
+                "a",
+                "( b )",
+                "form value f",
+                "value f",
+                "?r",
+                "value x with",
+                "i = 1 to 3",
+                "while i < 2",
+            ]
+        );
+    }
+
+    fn parse_recovering(source: &str) -> (Program, Vec<String>) {
+        let mut parser = RexxParser::new(source);
+        let (program, diagnostics) = parser.parse_recovering();
+        let codes = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.code.unwrap().to_string())
+            .collect();
+        (program, codes)
+    }
+
+    #[test]
+    fn recover_at_clause_boundaries() {
+        let (program, codes) = parse_recovering(
+            "x = (1\nsay 'ok'\ndo i = 1 to\n  say i; call; say i\nend\nsay 'a\nsay 'done'",
+        );
+        assert_eq!(codes, vec!["36", "35.1", "19.2", "6.2"]);
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [
+                InstructionKind::Say(_),
+                InstructionKind::Do { body, .. },
+                InstructionKind::Say(_),
+                InstructionKind::Say(_),
+            ] if body.len() == 2
+        ));
+    }
+
+    #[test]
+    fn recover_inside_groups() {
+        let (program, codes) =
+            parse_recovering("do\n  if a then\nend j\nselect\n  say 1\nend\ndo\n  say 2");
+        assert_eq!(codes, vec!["10.5", "10.3", "7.1", "14.1"]);
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [
+                InstructionKind::Do { end_name: Some(_), .. },
+                InstructionKind::Select { .. },
+                InstructionKind::Do { body, .. },
+            ] if body.len() == 1
+        ));
+    }
+
+    #[test]
+    fn parse_stops_at_first_error() {
+        assert!(matches!(
+            parse("call\nx = (1"),
+            Err(ParseError::ExpectedRoutineName(_))
+        ));
+    }
+}
@@ -0,0 +1,354 @@
+use crate::ast::{
+    Expression, ExpressionKind, InstructionKind, ParseCase, ParseSource, ParseSourceKind, Template,
+    TemplateElement, TemplateElementKind,
+};
+use crate::lexer::{SymbolKind, Token, TokenType};
+
+use super::{ParseError, ParseResult, RexxParser};
+
+impl RexxParser<'_> {
+    /// Parses a PARSE instruction after its keyword.
+    pub(super) fn parse_parse(&mut self) -> ParseResult<InstructionKind> {
+        let mut case = None;
+        let mut caseless = false;
+        loop {
+            let token = self.peek_significant().clone();
+            match self.keyword(&token).as_str() {
+                "UPPER" | "LOWER" if case.is_none() => {
+                    case = Some(if self.keyword(&token) == "UPPER" {
+                        ParseCase::Upper
+                    } else {
+                        ParseCase::Lower
+                    });
+                }
+                "CASELESS" if !caseless => caseless = true,
+                _ => break,
+            }
+            self.skip_whitespace();
+            self.advance();
+        }
+
+        self.skip_whitespace();
+        let start = self.position;
+        let token = self.advance();
+        let kind = match self.keyword(&token).as_str() {
+            "ARG" => ParseSourceKind::Arg,
+            "LINEIN" => ParseSourceKind::Linein,
+            "PULL" => ParseSourceKind::Pull,
+            "SOURCE" => ParseSourceKind::Source,
+            "VERSION" => ParseSourceKind::Version,
+            "VALUE" => {
+                let expression = self.parse_optional_expression(&["WITH"])?;
+                if self.keyword(self.peek_significant()) != "WITH" {
+                    return Err(ParseError::MissingWith(self.peek_significant().clone()));
+                }
+                self.skip_whitespace();
+                self.advance();
+                ParseSourceKind::Value(expression)
+            }
+            "VAR" => {
+                let name = self.peek_significant().clone();
+                if !is_variable(&name) {
+                    return Err(ParseError::ExpectedSymbol {
+                        keyword: Box::new(token),
+                        found: name,
+                    });
+                }
+                self.skip_whitespace();
+                self.advance();
+                ParseSourceKind::Var(name)
+            }
+            _ => return Err(ParseError::InvalidParseKeyword(token)),
+        };
+        let source = ParseSource {
+            kind,
+            span: self.span_from(start),
+        };
+        let templates = self.parse_template_list()?;
+        Ok(InstructionKind::Parse {
+            case,
+            caseless,
+            source,
+            templates,
+        })
+    }
+
+    /// Parses comma-separated templates up to the end of the clause.
+    pub(super) fn parse_template_list(&mut self) -> ParseResult<Vec<Template>> {
+        let mut templates = Vec::new();
+        let mut elements = Vec::new();
+        let mut template_start = self.position;
+        loop {
+            self.skip_whitespace();
+            let start = self.position;
+            let token = self.peek().clone();
+            let kind = match token.token_type {
+                TokenType::EOL | TokenType::Semicolon | TokenType::EOS => break,
+                TokenType::Comma => {
+                    templates.push(Template {
+                        elements: std::mem::take(&mut elements),
+                        span: self.span_from(template_start),
+                    });
+                    self.advance();
+                    template_start = self.position;
+                    continue;
+                }
+                TokenType::Symbol(SymbolKind::Constant) if self.get_text(&token) == "." => {
+                    self.advance();
+                    TemplateElementKind::Placeholder(token)
+                }
+                TokenType::Symbol(SymbolKind::Number) => {
+                    self.advance();
+                    TemplateElementKind::AbsolutePosition(Expression {
+                        span: token.range.clone(),
+                        kind: ExpressionKind::Symbol(token),
+                    })
+                }
+                TokenType::Symbol(SymbolKind::Constant) => {
+                    return Err(ParseError::InvalidTemplate(token))
+                }
+                TokenType::Symbol(_) => {
+                    self.advance();
+                    TemplateElementKind::Target(token)
+                }
+                TokenType::Literal | TokenType::HexString | TokenType::BinaryString => {
+                    self.advance();
+                    TemplateElementKind::LiteralPattern(token)
+                }
+                TokenType::LeftParen => {
+                    self.advance();
+                    TemplateElementKind::VariablePattern(self.parse_parenthesized(token)?)
+                }
+                TokenType::Equal => {
+                    self.advance();
+                    TemplateElementKind::AbsolutePosition(self.parse_position()?)
+                }
+                TokenType::Plus | TokenType::Minus => {
+                    self.advance();
+                    TemplateElementKind::RelativePosition {
+                        forward: token.token_type == TokenType::Plus,
+                        offset: self.parse_position()?,
+                    }
+                }
+                _ => return Err(ParseError::InvalidTemplate(token)),
+            };
+            elements.push(TemplateElement {
+                kind,
+                span: self.span_from(start),
+            });
+        }
+        templates.push(Template {
+            elements,
+            span: self.span_from(template_start),
+        });
+        self.expect_clause_end()?;
+        Ok(templates)
+    }
+
+    /// The number or `(expression)` after `=`, `+` or `-` in a template.
+    fn parse_position(&mut self) -> ParseResult<Expression> {
+        self.skip_whitespace();
+        let token = self.peek().clone();
+        match token.token_type {
+            TokenType::Symbol(SymbolKind::Number) => {
+                self.advance();
+                Ok(Expression {
+                    span: token.range.clone(),
+                    kind: ExpressionKind::Symbol(token),
+                })
+            }
+            TokenType::LeftParen => {
+                self.advance();
+                self.parse_parenthesized(token)
+            }
+            _ => Err(ParseError::InvalidPosition(token)),
+        }
+    }
+}
+
+/// Whether the token is a symbol that can name a variable.
+pub(super) fn is_variable(token: &Token) -> bool {
+    matches!(
+        token.token_type,
+        TokenType::Symbol(SymbolKind::Simple | SymbolKind::Stem | SymbolKind::Compound { .. })
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{ExpressionKind, InstructionKind, Program};
+    use crate::parser::kinds;
+
+    fn parse(source: &str) -> ParseResult<Program> {
+        let mut parser = RexxParser::new(source);
+        parser.parse()
+    }
+
+    /// Describes the elements of each template, one letter per element.
+    fn shapes(templates: &[Template]) -> Vec<String> {
+        templates
+            .iter()
+            .map(|template| {
+                template
+                    .elements
+                    .iter()
+                    .map(|element| match element.kind {
+                        TemplateElementKind::Target(_) => 'T',
+                        TemplateElementKind::Placeholder(_) => '.',
+                        TemplateElementKind::LiteralPattern(_) => 'L',
+                        TemplateElementKind::VariablePattern(_) => 'V',
+                        TemplateElementKind::AbsolutePosition(_) => 'A',
+                        TemplateElementKind::RelativePosition { forward: true, .. } => '+',
+                        TemplateElementKind::RelativePosition { forward: false, .. } => '-',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_parse_sources() {
+        let program = parse(
+            "parse arg a\nparse upper linein b\nparse lower caseless pull c\n\
+             parse source d\nparse value f(x) with e\nparse var line f\nparse version g",
+        )
+        .unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [
+                InstructionKind::Parse {
+                    case: None,
+                    caseless: false,
+                    source: ParseSource {
+                        kind: ParseSourceKind::Arg,
+                        ..
+                    },
+                    ..
+                },
+                InstructionKind::Parse {
+                    case: Some(ParseCase::Upper),
+                    source: ParseSource {
+                        kind: ParseSourceKind::Linein,
+                        ..
+                    },
+                    ..
+                },
+                InstructionKind::Parse {
+                    case: Some(ParseCase::Lower),
+                    caseless: true,
+                    source: ParseSource {
+                        kind: ParseSourceKind::Pull,
+                        ..
+                    },
+                    ..
+                },
+                InstructionKind::Parse {
+                    source: ParseSource {
+                        kind: ParseSourceKind::Source,
+                        ..
+                    },
+                    ..
+                },
+                InstructionKind::Parse {
+                    source: ParseSource {
+                        kind: ParseSourceKind::Value(Some(Expression {
+                            kind: ExpressionKind::FunctionCall { .. },
+                            ..
+                        })),
+                        ..
+                    },
+                    ..
+                },
+                InstructionKind::Parse {
+                    source: ParseSource {
+                        kind: ParseSourceKind::Var(_),
+                        ..
+                    },
+                    ..
+                },
+                InstructionKind::Parse {
+                    source: ParseSource {
+                        kind: ParseSourceKind::Version,
+                        ..
+                    },
+                    ..
+                },
+            ]
+        ));
+    }
+
+    #[test]
+    fn parse_templates() {
+        let program = parse(
+            "parse value x with a ',' b . 5 c =(n) d +3 e -(m) f (sep) g, h\nparse value with",
+        )
+        .unwrap();
+        let instructions = kinds(&program.instructions);
+        let [InstructionKind::Parse {
+            source:
+                ParseSource {
+                    kind: ParseSourceKind::Value(Some(_)),
+                    ..
+                },
+            templates,
+            ..
+        }, InstructionKind::Parse {
+            source:
+                ParseSource {
+                    kind: ParseSourceKind::Value(None),
+                    ..
+                },
+            templates: empty,
+            ..
+        }] = &instructions[..]
+        else {
+            panic!("unexpected instructions: {:?}", program.instructions);
+        };
+        assert_eq!(shapes(templates), vec!["TLT.ATAT+T-TVT", "T"]);
+        assert_eq!(shapes(empty), vec![""]);
+    }
+
+    #[test]
+    fn parse_short_forms() {
+        let program = parse("arg first, second rest\npull answer").unwrap();
+        let instructions = kinds(&program.instructions);
+        let [InstructionKind::Arg(arg), InstructionKind::Pull(pull)] = &instructions[..] else {
+            panic!("unexpected instructions: {:?}", program.instructions);
+        };
+        assert_eq!(shapes(arg), vec!["T", "TT"]);
+        assert_eq!(shapes(pull), vec!["T"]);
+    }
+
+    #[test]
+    fn parse_template_errors() {
+        assert!(matches!(
+            parse("parse foo a"),
+            Err(ParseError::InvalidParseKeyword(_))
+        ));
+        assert!(matches!(
+            parse("parse upper upper arg a"),
+            Err(ParseError::InvalidParseKeyword(_))
+        ));
+        assert!(matches!(
+            parse("parse value x a"),
+            Err(ParseError::MissingWith(_))
+        ));
+        assert!(matches!(
+            parse("parse var 'x' a"),
+            Err(ParseError::ExpectedSymbol { .. })
+        ));
+        assert!(matches!(
+            parse("parse arg a * b"),
+            Err(ParseError::InvalidTemplate(_))
+        ));
+        assert!(matches!(
+            parse("parse arg a + b"),
+            Err(ParseError::InvalidPosition(_))
+        ));
+        assert!(matches!(
+            parse("parse arg a (b c"),
+            Err(ParseError::UnmatchedParenthesis(_))
+        ));
+    }
+}
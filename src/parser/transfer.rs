@@ -0,0 +1,350 @@
+use crate::ast::{
+    CallTarget, CallTargetKind, Expression, InstructionKind, RoutineName, SignalTarget,
+    SignalTargetKind,
+};
+use crate::lexer::{Token, TokenType};
+
+use super::{ParseError, ParseResult, RexxParser};
+
+/// The conditions that CALL ON and CALL OFF accept.
+const CALL_CONDITIONS: &[&str] = &["ERROR", "FAILURE", "HALT", "NOTREADY"];
+
+/// The conditions that SIGNAL ON and SIGNAL OFF accept.
+const SIGNAL_CONDITIONS: &[&str] = &[
+    "ERROR",
+    "FAILURE",
+    "HALT",
+    "LOSTDIGITS",
+    "NOTREADY",
+    "NOVALUE",
+    "SYNTAX",
+];
+
+impl RexxParser<'_> {
+    /// Parses a CALL instruction after its keyword.
+    pub(super) fn parse_call(&mut self) -> ParseResult<InstructionKind> {
+        let start = self.position;
+        let token = self.peek_significant().clone();
+        match self.keyword(&token).as_str() {
+            "ON" | "OFF" => {
+                let on = self.keyword(&token) == "ON";
+                let condition =
+                    self.parse_condition(token, CALL_CONDITIONS, |keyword, found| {
+                        ParseError::InvalidCallCondition {
+                            keyword: Box::new(keyword),
+                            found,
+                        }
+                    })?;
+                let kind = if on {
+                    CallTargetKind::On {
+                        condition,
+                        name: self.parse_trap_name()?,
+                    }
+                } else {
+                    CallTargetKind::Off { condition }
+                };
+                let target = CallTarget {
+                    kind,
+                    span: self.span_from(start),
+                };
+                self.expect_clause_end_or(ParseError::UnexpectedToken)?;
+                return Ok(InstructionKind::Call {
+                    target,
+                    args: Vec::new(),
+                });
+            }
+            _ => {}
+        }
+
+        let name = match token.token_type {
+            TokenType::Symbol(_) => RoutineName::Symbol(token),
+            TokenType::Literal => RoutineName::Literal(token),
+            _ => return Err(ParseError::ExpectedRoutineName(token)),
+        };
+        self.skip_whitespace();
+        self.advance();
+        let target = CallTarget {
+            kind: CallTargetKind::Routine(name),
+            span: self.span_from(start),
+        };
+        let args = self.parse_call_arguments()?;
+        self.expect_clause_end_or(ParseError::UnexpectedToken)?;
+        Ok(InstructionKind::Call { target, args })
+    }
+
+    /// Parses a SIGNAL instruction after its keyword. Without VALUE, an
+    /// expression is only recognised when it does not start with a symbol or
+    /// a string, as those name a label.
+    pub(super) fn parse_signal(&mut self) -> ParseResult<InstructionKind> {
+        let start = self.position;
+        let token = self.peek_significant().clone();
+        let kind = match self.keyword(&token).as_str() {
+            "ON" | "OFF" => {
+                let on = self.keyword(&token) == "ON";
+                let condition =
+                    self.parse_condition(token, SIGNAL_CONDITIONS, |keyword, found| {
+                        ParseError::InvalidSignalCondition {
+                            keyword: Box::new(keyword),
+                            found,
+                        }
+                    })?;
+                if on {
+                    SignalTargetKind::On {
+                        condition,
+                        name: self.parse_trap_name()?,
+                    }
+                } else {
+                    SignalTargetKind::Off { condition }
+                }
+            }
+            "VALUE" => {
+                self.skip_whitespace();
+                self.advance();
+                SignalTargetKind::Value(self.parse_expression(&[])?)
+            }
+            _ => match token.token_type {
+                TokenType::Symbol(_) | TokenType::Literal => {
+                    self.skip_whitespace();
+                    self.advance();
+                    SignalTargetKind::Label(token)
+                }
+                _ if self.at_clause_end() => return Err(ParseError::ExpectedLabelName(token)),
+                _ => SignalTargetKind::Value(self.parse_expression(&[])?),
+            },
+        };
+        let span = self.span_from(start);
+        self.expect_clause_end_or(ParseError::UnexpectedToken)?;
+        Ok(InstructionKind::Signal(SignalTarget { kind, span }))
+    }
+
+    /// Parses the condition after the ON or OFF `keyword`, which must be one
+    /// of `conditions`.
+    fn parse_condition(
+        &mut self,
+        keyword: Token,
+        conditions: &[&str],
+        invalid: fn(Token, Token) -> ParseError,
+    ) -> ParseResult<Token> {
+        self.skip_whitespace();
+        self.advance();
+        let condition = self.peek_significant().clone();
+        if !conditions.contains(&self.keyword(&condition).as_str()) {
+            return Err(invalid(keyword, condition));
+        }
+        self.skip_whitespace();
+        self.advance();
+        Ok(condition)
+    }
+
+    /// Parses `NAME label` after the condition of an ON trap, if present.
+    fn parse_trap_name(&mut self) -> ParseResult<Option<Token>> {
+        if self.keyword(self.peek_significant()) != "NAME" {
+            return Ok(None);
+        }
+        self.skip_whitespace();
+        self.advance();
+        let name = self.peek_significant().clone();
+        if !matches!(name.token_type, TokenType::Symbol(_) | TokenType::Literal) {
+            return Err(ParseError::ExpectedTrapName(name));
+        }
+        self.skip_whitespace();
+        self.advance();
+        Ok(Some(name))
+    }
+
+    /// Parses the comma-separated arguments of a CALL up to the end of the
+    /// clause. Omitted arguments are `None`.
+    fn parse_call_arguments(&mut self) -> ParseResult<Vec<Option<Expression>>> {
+        let mut args = Vec::new();
+        if self.at_clause_end() {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.parse_optional_expression(&[])?);
+            if self.peek_significant().token_type != TokenType::Comma {
+                return Ok(args);
+            }
+            self.skip_whitespace();
+            self.advance();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{ExpressionKind, Program};
+    use crate::parser::kinds;
+
+    fn parse(source: &str) -> ParseResult<Program> {
+        let mut parser = RexxParser::new(source);
+        parser.parse()
+    }
+
+    #[test]
+    fn parse_call_routines() {
+        let program = parse("call sub\ncall 'EXTERN' a, , b\ncall f(1), x").unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [
+                InstructionKind::Call {
+                    target: CallTarget { kind: CallTargetKind::Routine(RoutineName::Symbol(_)), .. },
+                    args: none,
+                },
+                InstructionKind::Call {
+                    target: CallTarget { kind: CallTargetKind::Routine(RoutineName::Literal(_)), .. },
+                    args: omitted,
+                },
+                InstructionKind::Call {
+                    target: CallTarget { kind: CallTargetKind::Routine(RoutineName::Symbol(_)), .. },
+                    args: parenthesized,
+                },
+            ] if none.is_empty()
+                && matches!(omitted[..], [Some(_), None, Some(_)])
+                && matches!(
+                    parenthesized[..],
+                    [
+                        Some(Expression {
+                            kind: ExpressionKind::Parenthesized(_),
+                            ..
+                        }),
+                        Some(_),
+                    ]
+                )
+        ));
+    }
+
+    #[test]
+    fn parse_call_traps() {
+        let program = parse("call on error name handler\ncall on halt\ncall off notready").unwrap();
+        assert!(matches!(
+            &kinds(&program.instructions)[..],
+            [
+                InstructionKind::Call {
+                    target: CallTarget {
+                        kind: CallTargetKind::On { name: Some(_), .. },
+                        ..
+                    },
+                    ..
+                },
+                InstructionKind::Call {
+                    target: CallTarget {
+                        kind: CallTargetKind::On { name: None, .. },
+                        ..
+                    },
+                    ..
+                },
+                InstructionKind::Call {
+                    target: CallTarget {
+                        kind: CallTargetKind::Off { .. },
+                        ..
+                    },
+                    ..
+                },
+            ]
+        ));
+    }
+
+    #[test]
+    fn parse_call_errors() {
+        assert!(matches!(
+            parse("call"),
+            Err(ParseError::ExpectedRoutineName(_))
+        ));
+        assert!(matches!(
+            parse("call (x)"),
+            Err(ParseError::ExpectedRoutineName(_))
+        ));
+        assert!(matches!(
+            parse("call f a)"),
+            Err(ParseError::UnexpectedParenthesis(_))
+        ));
+        assert!(matches!(
+            parse("call on novalue"),
+            Err(ParseError::InvalidCallCondition { .. })
+        ));
+        assert!(matches!(
+            parse("call on error name (x)"),
+            Err(ParseError::ExpectedTrapName(_))
+        ));
+        assert!(matches!(
+            parse("call off error name x"),
+            Err(ParseError::UnexpectedToken(_))
+        ));
+    }
+
+    #[test]
+    fn parse_signal_targets() {
+        let source = "loop:\n  signal loop\nsignal value 'LO' || 'OP'\nsignal (name)\n\
+                      signal on syntax name recover\nsignal off novalue";
+        let program = parse(source).unwrap();
+        let instructions = kinds(&program.instructions);
+        let [label, signal, rest @ ..] = &instructions[..] else {
+            panic!("unexpected instructions: {:?}", program.instructions);
+        };
+        let (
+            InstructionKind::Label(label),
+            InstructionKind::Signal(SignalTarget {
+                kind: SignalTargetKind::Label(target),
+                ..
+            }),
+        ) = (label, signal)
+        else {
+            panic!("unexpected instructions: {:?}", program.instructions);
+        };
+        assert!(matches!(
+            rest,
+            [
+                InstructionKind::Signal(SignalTarget {
+                    kind: SignalTargetKind::Value(Expression {
+                        kind: ExpressionKind::Binary { .. },
+                        ..
+                    }),
+                    ..
+                }),
+                InstructionKind::Signal(SignalTarget {
+                    kind: SignalTargetKind::Value(Expression {
+                        kind: ExpressionKind::Parenthesized(_),
+                        ..
+                    }),
+                    ..
+                }),
+                InstructionKind::Signal(SignalTarget {
+                    kind: SignalTargetKind::On { name: Some(_), .. },
+                    ..
+                }),
+                InstructionKind::Signal(SignalTarget {
+                    kind: SignalTargetKind::Off { .. },
+                    ..
+                }),
+            ]
+        ));
+        let label_name = &source[label.range.start.index..label.range.end.index];
+        let target_name = &source[target.range.start.index..target.range.end.index];
+        assert_eq!(label_name, target_name);
+    }
+
+    #[test]
+    fn parse_signal_errors() {
+        assert!(matches!(
+            parse("signal"),
+            Err(ParseError::ExpectedLabelName(_))
+        ));
+        assert!(matches!(
+            parse("signal on lostdigits name"),
+            Err(ParseError::ExpectedTrapName(_))
+        ));
+        assert!(matches!(
+            parse("signal off"),
+            Err(ParseError::InvalidSignalCondition { .. })
+        ));
+        assert!(matches!(
+            parse("signal value"),
+            Err(ParseError::InvalidExpression(_))
+        ));
+        assert!(matches!(
+            parse("signal a b"),
+            Err(ParseError::UnexpectedToken(_))
+        ));
+    }
+}
@@ -0,0 +1,664 @@
+use crate::lexer::{Lexer, Token, TokenType};
+use crate::parser::{
+    binary_operator, clause_start, clause_tokens, keyword, ClauseStart, CONCATENATION_PRIORITY,
+};
+
+use super::{AstNode, SourceFile, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
+/// The keyword instructions whose operand is an expression.
+const EXPRESSION_INSTRUCTIONS: &[&str] = &[
+    "EXIT",
+    "INTERPRET",
+    "OPTIONS",
+    "PUSH",
+    "QUEUE",
+    "RETURN",
+    "SAY",
+];
+
+/// Builds the concrete syntax tree of a source. This never fails: whatever
+/// the parser would reject still ends up in some clause.
+pub fn parse(source: &str) -> SourceFile {
+    let mut lexer = Lexer::new(source);
+    let tokens = syntax_tokens(source, clause_tokens(lexer.tokenize()));
+    let mut builder = Builder {
+        source,
+        tokens,
+        position: 0,
+        stack: Vec::new(),
+    };
+    builder.start_node(SyntaxKind::SourceFile);
+    while builder.nth(0).is_some() {
+        builder.clause();
+    }
+    builder.bump_trivia();
+    let (kind, children) = builder.stack.pop().unwrap();
+    SourceFile::cast(SyntaxNode::new(kind, children)).unwrap()
+}
+
+/// Gives each token of the clauses its syntax kind. The final `EOS`, which
+/// has no text, is dropped.
+fn syntax_tokens(source: &str, tokens: Vec<Token>) -> Vec<(SyntaxKind, Token)> {
+    tokens
+        .into_iter()
+        .filter(|token| token.token_type != TokenType::EOS)
+        .map(|token| {
+            let kind = match token.token_type {
+                // A continuation is a blank that starts with its comma.
+                TokenType::Whitespace if source[token.range.start.index..].starts_with(',') => {
+                    SyntaxKind::Continuation
+                }
+                ref token_type => token_kind(token_type),
+            };
+            (kind, token)
+        })
+        .collect()
+}
+
+fn token_kind(token_type: &TokenType) -> SyntaxKind {
+    match token_type {
+        TokenType::Whitespace => SyntaxKind::Whitespace,
+        TokenType::Comment => SyntaxKind::Comment,
+        TokenType::Literal | TokenType::HexString | TokenType::BinaryString => SyntaxKind::String,
+        TokenType::Symbol(_) => SyntaxKind::Symbol,
+        TokenType::Comma => SyntaxKind::Comma,
+        TokenType::Colon => SyntaxKind::Colon,
+        TokenType::Semicolon => SyntaxKind::Semicolon,
+        TokenType::LeftParen => SyntaxKind::LParen,
+        TokenType::RightParen => SyntaxKind::RParen,
+        TokenType::Equal => SyntaxKind::Eq,
+        TokenType::EOL => SyntaxKind::Eol,
+        TokenType::Unknown | TokenType::EOS => SyntaxKind::Unknown,
+        _ => SyntaxKind::Operator,
+    }
+}
+
+struct Builder<'a> {
+    source: &'a str,
+    tokens: Vec<(SyntaxKind, Token)>,
+    position: usize,
+    /// The nodes being built, innermost last, with the children so far.
+    stack: Vec<(SyntaxKind, Vec<SyntaxElement>)>,
+}
+
+impl Builder<'_> {
+    fn start_node(&mut self, kind: SyntaxKind) {
+        self.stack.push((kind, Vec::new()));
+    }
+
+    /// Where a node could start in the current node, so that it can be
+    /// wrapped once it turns out to be the left operand of a binary
+    /// expression.
+    fn checkpoint(&self) -> usize {
+        self.stack.last().unwrap().1.len()
+    }
+
+    /// Starts a node that takes the children added since `checkpoint`.
+    fn start_node_at(&mut self, checkpoint: usize, kind: SyntaxKind) {
+        let children = self.stack.last_mut().unwrap().1.split_off(checkpoint);
+        self.stack.push((kind, children));
+    }
+
+    fn finish_node(&mut self) {
+        let (kind, children) = self.stack.pop().unwrap();
+        let node = SyntaxNode::new(kind, children);
+        self.push(SyntaxElement::Node(node));
+    }
+
+    fn push(&mut self, element: SyntaxElement) {
+        self.stack.last_mut().unwrap().1.push(element);
+    }
+
+    /// Adds the next token to the current node as the given kind.
+    fn bump_as(&mut self, kind: SyntaxKind) {
+        let (_, token) = &self.tokens[self.position];
+        let text = &self.source[token.range.start.index..token.range.end.index];
+        let token = SyntaxToken::new(kind, text, token.range.clone());
+        self.position += 1;
+        self.push(SyntaxElement::Token(token));
+    }
+
+    /// Adds the trivia before the next token, then that token.
+    fn bump(&mut self) {
+        self.bump_trivia();
+        self.bump_as(self.tokens[self.position].0);
+    }
+
+    /// Adds the trivia before the next token, then that token as the
+    /// keyword of the clause.
+    fn bump_keyword(&mut self) {
+        self.bump_trivia();
+        self.bump_as(SyntaxKind::Keyword);
+    }
+
+    fn bump_trivia(&mut self) {
+        while self
+            .tokens
+            .get(self.position)
+            .is_some_and(|(kind, _)| kind.is_trivia())
+        {
+            self.bump_as(self.tokens[self.position].0);
+        }
+    }
+
+    /// The `n`th token from the current position, not counting trivia.
+    fn nth_token(&self, n: usize) -> Option<&Token> {
+        self.tokens[self.position..]
+            .iter()
+            .filter(|(kind, _)| !kind.is_trivia())
+            .nth(n)
+            .map(|(_, token)| token)
+    }
+
+    fn nth(&self, n: usize) -> Option<SyntaxKind> {
+        self.tokens[self.position..]
+            .iter()
+            .map(|(kind, _)| *kind)
+            .filter(|kind| !kind.is_trivia())
+            .nth(n)
+    }
+
+    /// What the clause that starts at the `n`th token is.
+    fn clause_start(&self, n: usize) -> ClauseStart {
+        match self.nth_token(n) {
+            Some(first) => clause_start(self.source, first, self.nth_token(n + 1)),
+            None => ClauseStart::Null,
+        }
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.clause_start(0), ClauseStart::Keyword(found) if found == keyword)
+    }
+
+    fn at_then(&self) -> bool {
+        self.nth_token(0)
+            .is_some_and(|token| keyword(self.source, token) == "THEN")
+    }
+
+    fn at_terminator(&self) -> bool {
+        matches!(self.nth(0), Some(SyntaxKind::Semicolon | SyntaxKind::Eol))
+    }
+
+    fn at_clause_end(&self) -> bool {
+        self.nth(0).is_none() || self.at_terminator()
+    }
+
+    fn clause(&mut self) {
+        match self.clause_start(0) {
+            ClauseStart::Null => {
+                self.start_node(SyntaxKind::NullClause);
+                self.bump();
+                self.finish_node();
+            }
+            ClauseStart::Label => {
+                self.start_node(SyntaxKind::Label);
+                self.bump();
+                self.bump();
+                self.finish_node();
+            }
+            ClauseStart::Assignment => {
+                self.start_node(SyntaxKind::Assignment);
+                self.bump();
+                self.bump();
+                self.expression(false);
+                self.rest_of_clause(false);
+                self.terminator();
+                self.finish_node();
+            }
+            ClauseStart::Keyword("DO") => self.group(SyntaxKind::DoGroup, SyntaxKind::DoClause),
+            ClauseStart::Keyword("SELECT") => {
+                self.group(SyntaxKind::SelectGroup, SyntaxKind::SelectClause)
+            }
+            ClauseStart::Keyword("IF") => self.conditional(SyntaxKind::IfInstruction),
+            ClauseStart::Keyword("WHEN") => self.conditional(SyntaxKind::When),
+            ClauseStart::Keyword("THEN") => self.branch(SyntaxKind::ThenBranch),
+            ClauseStart::Keyword("ELSE") => self.branch(SyntaxKind::ElseBranch),
+            ClauseStart::Keyword("OTHERWISE") => self.otherwise(),
+            ClauseStart::Keyword("END") => self.keyword_clause(SyntaxKind::End),
+            ClauseStart::Keyword(keyword) if EXPRESSION_INSTRUCTIONS.contains(&keyword) => {
+                self.start_node(SyntaxKind::Instruction);
+                self.bump_keyword();
+                self.expression(false);
+                self.rest_of_clause(false);
+                self.terminator();
+                self.finish_node();
+            }
+            ClauseStart::Keyword(_) => self.keyword_clause(SyntaxKind::Instruction),
+            ClauseStart::Command => {
+                self.start_node(SyntaxKind::Command);
+                self.expression(false);
+                self.rest_of_clause(false);
+                self.terminator();
+                self.finish_node();
+            }
+        }
+    }
+
+    /// A clause with a keyword and a flat list of operands.
+    fn keyword_clause(&mut self, kind: SyntaxKind) {
+        self.start_node(kind);
+        self.bump_keyword();
+        self.operands();
+        self.terminator();
+        self.finish_node();
+    }
+
+    /// A DO or SELECT group: its first clause, the clauses inside it and
+    /// the END that closes it, if there is one before the source ends.
+    fn group(&mut self, group: SyntaxKind, clause: SyntaxKind) {
+        self.start_node(group);
+        self.keyword_clause(clause);
+        self.clauses_to_end();
+        if self.nth(0).is_some() {
+            self.keyword_clause(SyntaxKind::End);
+        }
+        self.finish_node();
+    }
+
+    /// Adds clauses up to the END of the enclosing group, which is left for
+    /// it, or up to the end of the source.
+    fn clauses_to_end(&mut self) {
+        while self.nth(0).is_some() && !self.at_keyword("END") {
+            self.clause();
+        }
+    }
+
+    /// IF or WHEN with its condition and the branches after it. An IF also
+    /// takes the ELSE that follows its THEN branch.
+    fn conditional(&mut self, kind: SyntaxKind) {
+        self.start_node(kind);
+        self.bump_keyword();
+        self.expression(true);
+        self.rest_of_clause(true);
+        if !self.at_then() {
+            self.terminator();
+        }
+        if self.null_clauses_to("THEN") {
+            self.branch(SyntaxKind::ThenBranch);
+            if kind == SyntaxKind::IfInstruction && self.null_clauses_to("ELSE") {
+                self.branch(SyntaxKind::ElseBranch);
+            }
+        }
+        self.finish_node();
+    }
+
+    /// Adds the null clauses before a clause that starts with `keyword`
+    /// and tells whether there is one. Nothing is added otherwise.
+    fn null_clauses_to(&mut self, keyword: &str) -> bool {
+        let mut n = 0;
+        while matches!(self.nth(n), Some(SyntaxKind::Semicolon | SyntaxKind::Eol)) {
+            n += 1;
+        }
+        if !matches!(self.clause_start(n), ClauseStart::Keyword(found) if found == keyword) {
+            return false;
+        }
+        for _ in 0..n {
+            self.bump();
+        }
+        true
+    }
+
+    /// THEN or ELSE and the instruction after it. An END is left for the
+    /// group that it closes.
+    fn branch(&mut self, kind: SyntaxKind) {
+        self.start_node(kind);
+        self.bump_keyword();
+        while self.at_terminator() {
+            self.bump();
+        }
+        if self.nth(0).is_some() && !self.at_keyword("END") {
+            self.clause();
+        }
+        self.finish_node();
+    }
+
+    /// OTHERWISE, with the clauses up to the END when it is in a SELECT.
+    fn otherwise(&mut self) {
+        let in_select = matches!(self.stack.last(), Some((SyntaxKind::SelectGroup, _)));
+        self.start_node(SyntaxKind::Otherwise);
+        self.bump_keyword();
+        if in_select {
+            self.clauses_to_end();
+        }
+        self.finish_node();
+    }
+
+    /// Adds an expression, if one starts here. With `stop_at_then`, a THEN
+    /// ends it, as in the condition of an IF or a WHEN.
+    fn expression(&mut self, stop_at_then: bool) {
+        self.binary(0, stop_at_then);
+    }
+
+    /// Adds an expression whose operators have at least `min_priority`, and
+    /// tells whether there was one.
+    fn binary(&mut self, min_priority: u8, stop_at_then: bool) -> bool {
+        self.bump_trivia();
+        let checkpoint = self.checkpoint();
+        if !self.prefix(stop_at_then) {
+            return false;
+        }
+        loop {
+            let operator = self
+                .nth_token(0)
+                .and_then(|token| binary_operator(&token.token_type));
+            let priority = match operator {
+                Some((_, priority)) => priority,
+                None if self.starts_term(stop_at_then) => CONCATENATION_PRIORITY,
+                None => break,
+            };
+            if priority < min_priority {
+                break;
+            }
+            self.start_node_at(checkpoint, SyntaxKind::BinaryExpr);
+            if operator.is_some() {
+                self.bump();
+            }
+            self.binary(priority + 1, stop_at_then);
+            self.finish_node();
+        }
+        true
+    }
+
+    fn prefix(&mut self, stop_at_then: bool) -> bool {
+        let is_prefix = self.nth_token(0).is_some_and(|token| {
+            matches!(
+                token.token_type,
+                TokenType::Plus | TokenType::Minus | TokenType::Not
+            )
+        });
+        if !is_prefix {
+            return self.term(stop_at_then);
+        }
+        self.bump_trivia();
+        self.start_node(SyntaxKind::PrefixExpr);
+        self.bump();
+        self.prefix(stop_at_then);
+        self.finish_node();
+        true
+    }
+
+    fn starts_term(&self, stop_at_then: bool) -> bool {
+        match self.nth_token(0).map(|token| &token.token_type) {
+            Some(TokenType::Symbol(_)) => !(stop_at_then && self.at_then()),
+            Some(
+                TokenType::Literal
+                | TokenType::HexString
+                | TokenType::BinaryString
+                | TokenType::LeftParen,
+            ) => true,
+            _ => false,
+        }
+    }
+
+    fn term(&mut self, stop_at_then: bool) -> bool {
+        if !self.starts_term(stop_at_then) {
+            return false;
+        }
+        self.bump_trivia();
+        let (_, token) = &self.tokens[self.position];
+        // A function call needs the `(` right after its name.
+        let call = matches!(token.token_type, TokenType::Symbol(_) | TokenType::Literal)
+            && self
+                .tokens
+                .get(self.position + 1)
+                .is_some_and(|(kind, _)| *kind == SyntaxKind::LParen);
+        let kind = match token.token_type {
+            TokenType::LeftParen => SyntaxKind::ParenExpr,
+            _ if call => SyntaxKind::CallExpr,
+            TokenType::Symbol(_) => SyntaxKind::SymbolExpr,
+            _ => SyntaxKind::StringExpr,
+        };
+        self.start_node(kind);
+        self.bump();
+        match kind {
+            SyntaxKind::ParenExpr => {
+                self.expression(false);
+                if self.nth(0) == Some(SyntaxKind::RParen) {
+                    self.bump();
+                }
+            }
+            SyntaxKind::CallExpr => self.arguments(),
+            _ => {}
+        }
+        self.finish_node();
+        true
+    }
+
+    /// The arguments of a function call, from `(` to `)`.
+    fn arguments(&mut self) {
+        self.start_node(SyntaxKind::ArgList);
+        self.bump();
+        if self.nth(0) != Some(SyntaxKind::RParen) {
+            loop {
+                self.bump_trivia();
+                self.start_node(SyntaxKind::Arg);
+                self.expression(false);
+                self.finish_node();
+                if self.nth(0) != Some(SyntaxKind::Comma) {
+                    break;
+                }
+                self.bump();
+            }
+        }
+        if self.nth(0) == Some(SyntaxKind::RParen) {
+            self.bump();
+        }
+        self.finish_node();
+    }
+
+    /// Adds the tokens that are left in the clause as they are, up to its
+    /// end or, with `stop_at_then`, up to a THEN.
+    fn rest_of_clause(&mut self, stop_at_then: bool) {
+        while !(self.at_clause_end() || stop_at_then && self.at_then()) {
+            self.bump();
+        }
+    }
+
+    /// Adds the tokens up to the end of the clause as an `Operands` node,
+    /// if there are any. Parentheses become nested groups.
+    fn operands(&mut self) {
+        if self.at_clause_end() {
+            return;
+        }
+        self.bump_trivia();
+        self.start_node(SyntaxKind::Operands);
+        let mut depth = 0;
+        while !self.at_clause_end() {
+            match self.nth(0) {
+                Some(SyntaxKind::LParen) => {
+                    self.bump_trivia();
+                    self.start_node(SyntaxKind::ParenGroup);
+                    self.bump();
+                    self.start_node(SyntaxKind::Operands);
+                    depth += 1;
+                }
+                Some(SyntaxKind::RParen) if depth > 0 => {
+                    self.bump_trivia();
+                    self.finish_node();
+                    self.bump();
+                    self.finish_node();
+                    depth -= 1;
+                }
+                _ => self.bump(),
+            }
+        }
+        for _ in 0..depth {
+            self.finish_node();
+            self.finish_node();
+        }
+        self.finish_node();
+    }
+
+    fn terminator(&mut self) {
+        if self.at_terminator() {
+            self.bump();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::{BinaryExpr, Clause, DoGroup, Expr};
+
+    #[test]
+    fn round_trip() {
+        for source in [
+            "",
+            "say 'a' /* comment */ ;; x = 1\n",
+            "  /* multi\n   line */ call f 1,\n     2\r\n",
+            "if (a then say 'é' ~ \"unterminated\nend )",
+            "do i = 1 to 3\n  select\n    when i = 1 then nop\n  end\nend i\nend",
+            "otherwise; then; else x = f(a b ~, (c\nif a then\n",
+            "select; otherwise nop; when 1 then; end; say -(+",
+        ] {
+            assert_eq!(parse(source).syntax().to_string(), source);
+        }
+    }
+
+    #[test]
+    fn build_clauses() {
+        let file = parse("/* doc */\nstart: x = f(1, (2))\nif x then\n  say x; else nop");
+        assert_eq!(
+            format!("{:?}", file.syntax()),
+            r#"SourceFile
+  NullClause
+    Comment "/* doc */"
+    Eol "\n"
+  Label
+    Symbol "start"
+    Colon ":"
+  Assignment
+    Whitespace " "
+    Symbol "x"
+    Whitespace " "
+    Eq "="
+    Whitespace " "
+    CallExpr
+      Symbol "f"
+      ArgList
+        LParen "("
+        Arg
+          SymbolExpr
+            Symbol "1"
+        Comma ","
+        Whitespace " "
+        Arg
+          ParenExpr
+            LParen "("
+            SymbolExpr
+              Symbol "2"
+            RParen ")"
+        RParen ")"
+    Eol "\n"
+  IfInstruction
+    Keyword "if"
+    Whitespace " "
+    SymbolExpr
+      Symbol "x"
+    ThenBranch
+      Whitespace " "
+      Keyword "then"
+      Eol "\n"
+      Instruction
+        Whitespace "  "
+        Keyword "say"
+        Whitespace " "
+        SymbolExpr
+          Symbol "x"
+        Semicolon ";"
+    ElseBranch
+      Whitespace " "
+      Keyword "else"
+      Instruction
+        Whitespace " "
+        Keyword "nop"
+"#
+        );
+    }
+
+    #[test]
+    fn build_groups() {
+        let file = parse("do i = 1\n  do; end\nend i; end");
+        let clauses = file.clauses().collect::<Vec<_>>();
+        let [Clause::DoGroup(outer), Clause::End(stray)] = &clauses[..] else {
+            panic!("unexpected clauses: {clauses:?}");
+        };
+        let inner = outer.clauses().collect::<Vec<_>>();
+        // The END of a group is a clause too.
+        assert!(matches!(&inner[..], [Clause::DoGroup(_), Clause::End(_)]));
+        let do_clause = outer.do_clause().unwrap();
+        assert_eq!(do_clause.keyword_token().unwrap().text(), "do");
+        assert_eq!(do_clause.operands().unwrap().syntax().to_string(), "i = 1");
+        let end = outer.end().unwrap();
+        assert_eq!(end.operands().unwrap().syntax().to_string(), "i");
+        assert!(end.semicolon_token().is_some());
+        assert!(stray.keyword_token().is_some());
+        assert!(DoGroup::cast(stray.syntax().clone()).is_none());
+    }
+
+    #[test]
+    fn build_expressions() {
+        let file = parse("say -a + b * c 'x'(1,,2) = d");
+        let [Clause::Instruction(say)] = &file.clauses().collect::<Vec<_>>()[..] else {
+            panic!("unexpected tree: {:?}", file.syntax());
+        };
+        // The comparison binds last, and blank concatenation before it.
+        let Some(Expr::BinaryExpr(comparison)) = say.expr() else {
+            panic!("unexpected tree: {:?}", say.syntax());
+        };
+        assert_eq!(comparison.eq_token().unwrap().text(), "=");
+        assert_eq!(comparison.rhs().unwrap().syntax().to_string(), "d");
+        let concatenation = BinaryExpr::cast(comparison.lhs().unwrap().syntax().clone()).unwrap();
+        assert!(concatenation.operator_token().is_none());
+        let Some(Expr::CallExpr(call)) = concatenation.rhs() else {
+            panic!("unexpected tree: {:?}", concatenation.syntax());
+        };
+        assert_eq!(call.string_token().unwrap().text(), "'x'");
+        let args = call.arg_list().unwrap().args().collect::<Vec<_>>();
+        assert_eq!(args.len(), 3);
+        assert!(args[1].expr().is_none());
+        let Some(Expr::BinaryExpr(sum)) = concatenation.lhs() else {
+            panic!("unexpected tree: {:?}", concatenation.syntax());
+        };
+        assert!(matches!(sum.lhs(), Some(Expr::PrefixExpr(_))));
+        assert_eq!(sum.rhs().unwrap().syntax().to_string(), "b * c");
+        // Tokens that cannot continue the expression stay in the clause.
+        let file = parse("x = a ~ b");
+        assert_eq!(file.syntax().to_string(), "x = a ~ b");
+        let [Clause::Assignment(assignment)] = &file.clauses().collect::<Vec<_>>()[..] else {
+            panic!("unexpected tree: {:?}", file.syntax());
+        };
+        assert_eq!(assignment.expr().unwrap().syntax().to_string(), "a");
+    }
+
+    #[test]
+    fn build_if_and_select() {
+        let file = parse(
+            "if a then if b then nop\n\n else say 1\nelse = 2\n\
+             select; when c\n then do; end; otherwise say 2; say 3; end",
+        );
+        let clauses = file.clauses().collect::<Vec<_>>();
+        let [Clause::IfInstruction(outer), Clause::Assignment(_), Clause::SelectGroup(select)] =
+            &clauses[..]
+        else {
+            panic!("unexpected tree: {:?}", file.syntax());
+        };
+        // The ELSE belongs to the inner IF, and `else = 2` is an assignment.
+        assert!(outer.else_branch().is_none());
+        let Some(Clause::IfInstruction(inner)) = outer.then_branch().unwrap().clause() else {
+            panic!("unexpected tree: {:?}", outer.syntax());
+        };
+        assert_eq!(inner.condition().unwrap().syntax().to_string(), "b");
+        let else_branch = inner.else_branch().unwrap();
+        assert!(matches!(else_branch.clause(), Some(Clause::Instruction(_))));
+        let clauses = select.clauses().collect::<Vec<_>>();
+        let [Clause::When(when), Clause::Otherwise(otherwise), Clause::End(_)] = &clauses[..]
+        else {
+            panic!("unexpected tree: {:?}", select.syntax());
+        };
+        let then_branch = when.then_branch().unwrap();
+        assert!(matches!(then_branch.clause(), Some(Clause::DoGroup(_))));
+        assert_eq!(otherwise.clauses().count(), 2);
+    }
+}
@@ -0,0 +1,3 @@
+use super::{support, AstChildren, AstNode, SyntaxNode, SyntaxToken};
+
+include!(concat!(env!("OUT_DIR"), "/syntax.rs"));
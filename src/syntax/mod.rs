@@ -0,0 +1,59 @@
+//! A lossless concrete syntax tree. Unlike the AST, it keeps every token of
+//! the source, so it can be printed back as it was written. The typed nodes
+//! are generated by `build.rs` from `rexx.ungram`.
+
+mod builder;
+mod generated;
+mod tree;
+
+use std::marker::PhantomData;
+
+pub use builder::parse;
+pub use generated::*;
+pub use tree::{SyntaxElement, SyntaxNode, SyntaxToken};
+
+/// A typed view of a syntax node.
+pub trait AstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+    fn cast(syntax: SyntaxNode) -> Option<Self>;
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+/// The children of a node that can be cast to `N`.
+pub struct AstChildren<N> {
+    children: std::vec::IntoIter<SyntaxNode>,
+    node: PhantomData<N>,
+}
+
+impl<N: AstNode> Iterator for AstChildren<N> {
+    type Item = N;
+
+    fn next(&mut self) -> Option<N> {
+        self.children.find_map(N::cast)
+    }
+}
+
+mod support {
+    use super::{AstChildren, AstNode, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
+    pub(super) fn child<N: AstNode>(parent: &SyntaxNode) -> Option<N> {
+        children(parent).next()
+    }
+
+    pub(super) fn children<N: AstNode>(parent: &SyntaxNode) -> AstChildren<N> {
+        AstChildren {
+            children: parent.children().cloned().collect::<Vec<_>>().into_iter(),
+            node: std::marker::PhantomData,
+        }
+    }
+
+    pub(super) fn token(parent: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+        parent
+            .children_with_tokens()
+            .iter()
+            .find_map(|element| match element {
+                SyntaxElement::Token(token) if token.kind() == kind => Some(token.clone()),
+                _ => None,
+            })
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::lexer::Range;
+
+use super::SyntaxKind;
+
+/// A node of the concrete syntax tree. Nodes are immutable and cheap to
+/// clone; they do not know their parent.
+#[derive(Clone, PartialEq)]
+pub struct SyntaxNode(Arc<NodeData>);
+
+#[derive(PartialEq)]
+struct NodeData {
+    kind: SyntaxKind,
+    children: Vec<SyntaxElement>,
+}
+
+/// A token of the concrete syntax tree, with its own text.
+#[derive(Clone, PartialEq)]
+pub struct SyntaxToken(Arc<TokenData>);
+
+#[derive(PartialEq)]
+struct TokenData {
+    kind: SyntaxKind,
+    text: String,
+    range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new(kind: SyntaxKind, children: Vec<SyntaxElement>) -> SyntaxNode {
+        SyntaxNode(Arc::new(NodeData { kind, children }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind
+    }
+
+    pub fn children_with_tokens(&self) -> &[SyntaxElement] {
+        &self.0.children
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.0.children.iter().filter_map(|element| match element {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The tokens of this node and of all its descendants, in source order.
+    pub fn descendant_tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens(&self, tokens: &mut Vec<SyntaxToken>) {
+        for element in &self.0.children {
+            match element {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token.clone()),
+            }
+        }
+    }
+
+    /// The range from the first to the last token of the node, or `None`
+    /// for a node without tokens.
+    pub fn range(&self) -> Option<Range> {
+        let tokens = self.descendant_tokens();
+        Some(Range {
+            start: tokens.first()?.range().start.clone(),
+            end: tokens.last()?.range().end.clone(),
+        })
+    }
+}
+
+impl SyntaxToken {
+    pub fn new(kind: SyntaxKind, text: &str, range: Range) -> SyntaxToken {
+        SyntaxToken(Arc::new(TokenData {
+            kind,
+            text: text.to_string(),
+            range,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    pub fn range(&self) -> &Range {
+        &self.0.range
+    }
+}
+
+/// Prints the source text that the node covers.
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for element in &self.0.children {
+            match element {
+                SyntaxElement::Node(node) => write!(f, "{node}")?,
+                SyntaxElement::Token(token) => write!(f, "{}", token.text())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prints the tree with one node or token per line, indented by depth.
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indent = f.width().unwrap_or(0);
+        writeln!(f, "{:indent$}{:?}", "", self.kind())?;
+        for element in &self.0.children {
+            match element {
+                SyntaxElement::Node(node) => write!(f, "{:width$?}", node, width = indent + 2)?,
+                SyntaxElement::Token(token) => {
+                    writeln!(f, "{:width$}{:?}", "", token, width = indent + 2)?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {:?}", self.kind(), self.text())
+    }
+}